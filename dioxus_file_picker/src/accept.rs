@@ -0,0 +1,234 @@
+use std::{fmt, path::Path};
+
+/// The kinds of files a picker offers, in the spirit of the html `accept` attribute.
+///
+/// An empty spec, or one with `*/*`, accepts every file. Directories are never filtered by it. Can be built with
+/// `Accept::new().extension("csv").mime_type("image/*")` or parsed from `".csv,image/*"`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Accept {
+    entries: Vec<AcceptEntry>,
+}
+
+/// A single entry of an [Accept] spec.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AcceptEntry {
+    /// A file extension without the leading dot, e.g. `csv`. Matched case-insensitively.
+    Extension(String),
+    /// A MIME type, e.g. `text/csv`, or a wildcard over the subtype, e.g. `image/*`.
+    MimeType(String),
+}
+
+impl Accept {
+    /// An empty spec, which accepts every file.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accepts files with the extension, given with or without the leading dot.
+    pub fn extension(mut self, extension: impl AsRef<str>) -> Self {
+        let extension = extension.as_ref().trim().trim_start_matches('.');
+        self.entries
+            .push(AcceptEntry::Extension(extension.to_ascii_lowercase()));
+        self
+    }
+
+    /// Accepts files of the MIME type, e.g. `text/csv` or `image/*`.
+    pub fn mime_type(mut self, mime_type: impl AsRef<str>) -> Self {
+        let mime_type = mime_type.as_ref().trim();
+        self.entries
+            .push(AcceptEntry::MimeType(mime_type.to_ascii_lowercase()));
+        self
+    }
+
+    /// Parses a comma separated list in the format of the html `accept` attribute, e.g. `.csv,image/*`. Entries
+    /// containing a `/` are MIME types, everything else is an extension.
+    pub fn parse(spec: &str) -> Self {
+        spec.split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .fold(Self::new(), |accept, entry| {
+                if entry.contains('/') {
+                    accept.mime_type(entry)
+                } else {
+                    accept.extension(entry)
+                }
+            })
+    }
+
+    /// Returns true if every file is accepted: the spec is empty or has `*/*`.
+    pub fn is_any(&self) -> bool {
+        self.entries.is_empty() || self.entries.iter().any(AcceptEntry::is_any)
+    }
+
+    /// The entries of the spec, in the order they were added.
+    pub fn entries(&self) -> &[AcceptEntry] {
+        &self.entries
+    }
+
+    /// Returns true if the file at `path` is accepted. Only the file name is considered, the file is not read.
+    pub fn matches(&self, path: impl AsRef<Path>) -> bool {
+        self.is_any()
+            || self
+                .entries
+                .iter()
+                .any(|entry| entry.matches(path.as_ref()))
+    }
+
+    /// Formats the spec as the value of the html `accept` attribute.
+    pub fn to_html_attribute(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl AcceptEntry {
+    /// Returns true for `*/*`, which accepts every file, even those without a known MIME type.
+    pub fn is_any(&self) -> bool {
+        matches!(self, AcceptEntry::MimeType(mime_type) if mime_type == "*/*" || mime_type == "*")
+    }
+
+    /// Returns true if the file at `path` is accepted by this entry.
+    pub fn matches(&self, path: &Path) -> bool {
+        match self {
+            AcceptEntry::Extension(extension) => path
+                .extension()
+                .is_some_and(|e| e.to_string_lossy().eq_ignore_ascii_case(extension)),
+            AcceptEntry::MimeType(_) if self.is_any() => true,
+            AcceptEntry::MimeType(mime_type) => {
+                let (top, sub) = mime_type.split_once('/').unwrap_or((mime_type, "*"));
+                mime_guess::from_path(path).iter().any(|guess| {
+                    guess.type_().as_str().eq_ignore_ascii_case(top)
+                        && (sub == "*" || guess.subtype().as_str().eq_ignore_ascii_case(sub))
+                })
+            }
+        }
+    }
+
+    /// The extensions, without the leading dot, this entry corresponds to. Used where only extensions can be
+    /// expressed, such as native file dialogs.
+    pub fn extensions(&self) -> Vec<String> {
+        match self {
+            AcceptEntry::Extension(extension) => vec![extension.clone()],
+            AcceptEntry::MimeType(mime_type) => mime_guess::get_mime_extensions_str(mime_type)
+                .map(|extensions| extensions.iter().map(|e| e.to_string()).collect())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl fmt::Display for Accept {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, entry) in self.entries.iter().enumerate() {
+            if index != 0 {
+                f.write_str(",")?;
+            }
+            write!(f, "{entry}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for AcceptEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AcceptEntry::Extension(extension) => write!(f, ".{extension}"),
+            AcceptEntry::MimeType(mime_type) => f.write_str(mime_type),
+        }
+    }
+}
+
+impl From<&str> for Accept {
+    fn from(spec: &str) -> Self {
+        Self::parse(spec)
+    }
+}
+
+impl From<String> for Accept {
+    fn from(spec: String) -> Self {
+        Self::parse(&spec)
+    }
+}
+
+impl From<Vec<String>> for Accept {
+    fn from(entries: Vec<String>) -> Self {
+        Self::parse(&entries.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_extensions_and_mime_types() {
+        let accept = Accept::parse(" .CSV, txt ,image/*,, ");
+        assert_eq!(
+            accept.entries(),
+            [
+                AcceptEntry::Extension("csv".to_owned()),
+                AcceptEntry::Extension("txt".to_owned()),
+                AcceptEntry::MimeType("image/*".to_owned()),
+            ]
+        );
+        assert_eq!(Accept::parse(""), Accept::new());
+    }
+
+    #[test]
+    fn displays_as_the_html_attribute() {
+        let accept = Accept::new().extension("csv").mime_type("Image/PNG");
+        assert_eq!(accept.to_string(), ".csv,image/png");
+        assert_eq!(Accept::parse(&accept.to_html_attribute()), accept);
+    }
+
+    #[test]
+    fn empty_accepts_everything() {
+        assert!(Accept::new().is_any());
+        assert!(Accept::new().matches("anything.bin"));
+        assert!(Accept::new().matches("no_extension"));
+    }
+
+    #[test]
+    fn extensions_match_case_insensitively() {
+        let accept = Accept::parse(".csv");
+        assert!(accept.matches("data.CSV"));
+        assert!(!accept.matches("data.csv.bak"));
+        assert!(!accept.matches("csv"));
+    }
+
+    #[test]
+    fn mime_types_match_by_guess() {
+        let accept = Accept::parse("image/*");
+        assert!(accept.matches("photo.JPG"));
+        assert!(accept.matches("icon.png"));
+        assert!(!accept.matches("notes.txt"));
+        let accept = Accept::parse("image/png");
+        assert!(accept.matches("icon.png"));
+        assert!(!accept.matches("photo.jpg"));
+    }
+
+    #[test]
+    fn any_mime_type_accepts_everything() {
+        let accept = Accept::parse("*/*");
+        assert!(accept.is_any());
+        assert!(accept.matches("notes.txt"));
+        assert!(accept.matches("no_extension"));
+        assert!(Accept::parse(".csv,*/*").matches("photo.jpg"));
+    }
+
+    #[test]
+    fn extensions_of_entries() {
+        assert_eq!(
+            AcceptEntry::Extension("csv".to_owned()).extensions(),
+            ["csv"]
+        );
+        assert!(
+            AcceptEntry::MimeType("image/png".to_owned())
+                .extensions()
+                .contains(&"png".to_owned())
+        );
+        assert!(
+            AcceptEntry::MimeType("application/x-unknown-to-anyone".to_owned())
+                .extensions()
+                .is_empty()
+        );
+    }
+}
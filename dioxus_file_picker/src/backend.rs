@@ -0,0 +1,305 @@
+use std::{
+    fmt,
+    str::FromStr,
+    sync::{OnceLock, RwLock},
+};
+
+use dioxus::logger::tracing::{debug, warn};
+
+/// The environment variable support can set to force a backend on every picker of an app, e.g.
+/// `DIOXUS_FILE_PICKER_BACKEND=integrated`. Takes precedence over [set_backend_override] and the launcher props.
+pub const BACKEND_ENV_VAR: &str = "DIOXUS_FILE_PICKER_BACKEND";
+
+static BACKEND_OVERRIDE: RwLock<Option<Backend>> = RwLock::new(None);
+
+/// How a file picker is shown on desktop. Has no effect on web, which always uses the browser's file input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    /// The platform's file dialog, e.g. through the xdg-desktop-portal FileChooser on Linux.
+    Native,
+    /// The integrated file picker in a new window. Parsed from `integrated` or `windowed`.
+    Windowed,
+    /// The integrated file picker in an overlay over the current window.
+    Overlay,
+}
+
+impl Backend {
+    /// The backend requested by the `desktop_native` and `desktop_windowed` launcher props.
+    pub(crate) fn requested(desktop_native: bool, desktop_windowed: bool) -> Self {
+        if desktop_native {
+            Backend::Native
+        } else if desktop_windowed {
+            Backend::Windowed
+        } else {
+            Backend::Overlay
+        }
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Backend::Native => "native",
+            Backend::Windowed => "integrated",
+            Backend::Overlay => "overlay",
+        })
+    }
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "native" => Ok(Backend::Native),
+            "integrated" | "windowed" => Ok(Backend::Windowed),
+            "overlay" => Ok(Backend::Overlay),
+            other => Err(format!(
+                "Unknown file picker backend \"{other}\", expected one of native, integrated or overlay"
+            )),
+        }
+    }
+}
+
+/// Forces every picker of the app to use `backend`, regardless of the launcher props. [BACKEND_ENV_VAR] still takes
+/// precedence. Pass None to go back to the launcher props.
+pub fn set_backend_override(backend: Option<Backend>) {
+    *BACKEND_OVERRIDE
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = backend;
+}
+
+/// The backend forced by [BACKEND_ENV_VAR] or [set_backend_override], if any.
+pub fn backend_override() -> Option<Backend> {
+    static FROM_ENV: OnceLock<Option<Backend>> = OnceLock::new();
+    let from_env = *FROM_ENV.get_or_init(|| {
+        let value = std::env::var(BACKEND_ENV_VAR).ok()?;
+        value
+            .parse()
+            .inspect_err(|err| warn!("Ignoring {BACKEND_ENV_VAR}: {err}"))
+            .ok()
+    });
+    from_env.or_else(|| {
+        *BACKEND_OVERRIDE
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    })
+}
+
+/// What the environment offers for showing a file picker. Detected once per process.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BackendSupport {
+    /// If a display server is known. Always true on Windows and macOS.
+    pub has_display: bool,
+    /// If the app runs inside a Flatpak sandbox, where only the portal can reach the host file system.
+    pub in_flatpak: bool,
+    /// If a native file dialog can be shown. On Linux and the BSDs this means an xdg-desktop-portal FileChooser is
+    /// reachable on the session bus.
+    pub native_available: bool,
+}
+
+#[cfg(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+impl BackendSupport {
+    /// Detects what the environment offers. The result is cached, so only the first call does any work.
+    pub async fn detect() -> Self {
+        static SUPPORT: OnceLock<BackendSupport> = OnceLock::new();
+        if let Some(support) = SUPPORT.get() {
+            return *support;
+        }
+        let support = Self::detect_uncached().await;
+        debug!("Detected file picker backend support: {support:?}");
+        *SUPPORT.get_or_init(|| support)
+    }
+
+    #[cfg(any(target_os = "windows", target_os = "macos"))]
+    async fn detect_uncached() -> Self {
+        Self {
+            has_display: true,
+            in_flatpak: false,
+            native_available: true,
+        }
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    async fn detect_uncached() -> Self {
+        let has_display =
+            std::env::var_os("WAYLAND_DISPLAY").is_some() || std::env::var_os("DISPLAY").is_some();
+        let in_flatpak = std::path::Path::new("/.flatpak-info").exists()
+            || std::env::var_os("FLATPAK_ID").is_some();
+        Self {
+            has_display,
+            in_flatpak,
+            native_available: portal_file_chooser_available().await,
+        }
+    }
+
+    /// The backend to use for a picker that requested `requested`. An override is used as is, otherwise see
+    /// [BackendSupport::choose].
+    pub(crate) async fn resolve(requested: Backend) -> Backend {
+        if let Some(forced) = backend_override() {
+            debug!("Using the {forced} file picker backend, as it is forced");
+            return forced;
+        }
+        Self::detect().await.choose(requested)
+    }
+
+    /// The backend this environment can show for `requested`. A native request falls back to the integrated file
+    /// picker if no native dialog is available. Inside Flatpak the portal is used whenever it is reachable, as the
+    /// integrated file picker would only see the sandbox. An integrated request is then replaced with a warning,
+    /// forcing the backend with an override still gets the integrated file picker.
+    fn choose(self, requested: Backend) -> Backend {
+        match requested {
+            Backend::Native if !self.native_available => {
+                let fallback = if self.has_display {
+                    Backend::Windowed
+                } else {
+                    Backend::Overlay
+                };
+                if self.in_flatpak {
+                    warn!(
+                        "No xdg-desktop-portal FileChooser is available inside Flatpak. Using the {fallback} file \
+                         picker backend instead, which only sees the files of the sandbox"
+                    );
+                } else {
+                    warn!(
+                        "No native file dialog is available. Using the {fallback} file picker backend instead. Set \
+                         {BACKEND_ENV_VAR} to choose the backend explicitly"
+                    );
+                }
+                fallback
+            }
+            Backend::Windowed | Backend::Overlay if self.in_flatpak && self.native_available => {
+                warn!(
+                    "Using the native file picker backend instead of the requested {requested} one, as only the \
+                     portal reaches the host files from Flatpak. Set {BACKEND_ENV_VAR}={requested} to use it anyway"
+                );
+                Backend::Native
+            }
+            Backend::Windowed if !self.has_display => Backend::Overlay,
+            requested => requested,
+        }
+    }
+}
+
+/// Returns true if `org.freedesktop.portal.FileChooser` is reachable on the session bus.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+async fn portal_file_chooser_available() -> bool {
+    const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(2);
+
+    let query = async {
+        let connection = zbus::Connection::session().await?;
+        let proxy = zbus::Proxy::new(
+            &connection,
+            "org.freedesktop.portal.Desktop",
+            "/org/freedesktop/portal/desktop",
+            "org.freedesktop.portal.FileChooser",
+        )
+        .await?;
+        proxy.get_property::<u32>("version").await
+    };
+    match tokio::time::timeout(TIMEOUT, query).await {
+        Ok(Ok(version)) => {
+            debug!("Found xdg-desktop-portal FileChooser version {version}");
+            true
+        }
+        Ok(Err(err)) => {
+            debug!("No xdg-desktop-portal FileChooser: {err}");
+            false
+        }
+        Err(_) => {
+            debug!("Timed out looking for an xdg-desktop-portal FileChooser");
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DESKTOP: BackendSupport = BackendSupport {
+        has_display: true,
+        in_flatpak: false,
+        native_available: true,
+    };
+
+    #[test]
+    fn backends_parse_from_their_names() {
+        assert_eq!("native".parse(), Ok(Backend::Native));
+        assert_eq!(" Integrated ".parse(), Ok(Backend::Windowed));
+        assert_eq!("windowed".parse(), Ok(Backend::Windowed));
+        assert_eq!("OVERLAY".parse(), Ok(Backend::Overlay));
+        assert!("portal".parse::<Backend>().is_err());
+        for backend in [Backend::Native, Backend::Windowed, Backend::Overlay] {
+            assert_eq!(backend.to_string().parse(), Ok(backend));
+        }
+    }
+
+    #[test]
+    fn native_wins_over_windowed_in_the_props() {
+        assert_eq!(Backend::requested(true, true), Backend::Native);
+        assert_eq!(Backend::requested(true, false), Backend::Native);
+        assert_eq!(Backend::requested(false, true), Backend::Windowed);
+        assert_eq!(Backend::requested(false, false), Backend::Overlay);
+    }
+
+    #[test]
+    fn the_override_is_reset_with_none() {
+        set_backend_override(Some(Backend::Overlay));
+        assert_eq!(backend_override(), Some(Backend::Overlay));
+        set_backend_override(None);
+        assert_eq!(backend_override(), None);
+    }
+
+    #[test]
+    fn supported_requests_are_kept() {
+        for backend in [Backend::Native, Backend::Windowed, Backend::Overlay] {
+            assert_eq!(DESKTOP.choose(backend), backend);
+        }
+    }
+
+    #[test]
+    fn native_falls_back_to_the_integrated_picker() {
+        let no_native = BackendSupport {
+            native_available: false,
+            ..DESKTOP
+        };
+        assert_eq!(no_native.choose(Backend::Native), Backend::Windowed);
+        let headless = BackendSupport {
+            has_display: false,
+            ..no_native
+        };
+        assert_eq!(headless.choose(Backend::Native), Backend::Overlay);
+        assert_eq!(headless.choose(Backend::Windowed), Backend::Overlay);
+    }
+
+    #[test]
+    fn flatpak_prefers_the_portal() {
+        let flatpak = BackendSupport {
+            in_flatpak: true,
+            ..DESKTOP
+        };
+        assert_eq!(flatpak.choose(Backend::Windowed), Backend::Native);
+        assert_eq!(flatpak.choose(Backend::Overlay), Backend::Native);
+        let no_portal = BackendSupport {
+            native_available: false,
+            ..flatpak
+        };
+        assert_eq!(no_portal.choose(Backend::Native), Backend::Windowed);
+        assert_eq!(no_portal.choose(Backend::Overlay), Backend::Overlay);
+    }
+}
@@ -0,0 +1,437 @@
+use std::{
+    io,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use dioxus::prelude::*;
+use regex::{Regex, RegexBuilder};
+
+use crate::{
+    FileExplorer, FileSystem, FileSystemType,
+    explorer::is_within_roots,
+    filter::Highlighted,
+    walk::{WalkEntry, walk},
+};
+
+/// How deep below the current directory is searched
+const MAX_DEPTH: usize = 12;
+/// Larger files are skipped, they are rarely text worth searching
+const MAX_FILE_SIZE: u64 = 4 * 1024 * 1024;
+/// Files with a NUL byte in this many first bytes are taken as binary and skipped
+const BINARY_CHECK_LENGTH: usize = 8 * 1024;
+const MAX_HITS_PER_FILE: usize = 20;
+/// The search stops here, more are not worth reading through
+const MAX_HITS: usize = 2_000;
+/// How much of a line is shown around its first match
+const SNIPPET_BEFORE: usize = 60;
+const SNIPPET_LENGTH: usize = 200;
+/// How often progress is reported, in files
+const PROGRESS_INTERVAL: usize = 100;
+
+/// A search started from the search box.
+#[derive(Clone, Debug, PartialEq)]
+struct SearchRequest {
+    root: PathBuf,
+    text: String,
+    is_regex: bool,
+    match_case: bool,
+}
+
+impl SearchRequest {
+    fn regex(&self) -> Result<Regex, regex::Error> {
+        let pattern = if self.is_regex {
+            self.text.clone()
+        } else {
+            regex::escape(&self.text)
+        };
+        RegexBuilder::new(&pattern)
+            .case_insensitive(!self.match_case)
+            .size_limit(1 << 20)
+            .build()
+    }
+}
+
+/// A line that matched, shortened to a snippet around its first match.
+#[derive(Clone, Debug, PartialEq)]
+struct Hit {
+    /// Starting at 1
+    line: usize,
+    snippet: String,
+    highlights: Vec<Range<usize>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct FileHits {
+    entry: WalkEntry,
+    hits: Vec<Hit>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Progress {
+    searched: usize,
+    skipped: usize,
+}
+
+/// What the search thread sends back.
+enum SearchEvent {
+    Progress(Progress),
+    Found(FileHits),
+}
+
+/// Finds the text files below the current directory that contain a text or match a regex. Matches are listed as they
+/// are found, clicking a file adds it to the selection.
+#[component]
+pub(crate) fn ContentSearch(explorer: Signal<FileExplorer>, on_close: Callback) -> Element {
+    let mut text = use_signal(String::new);
+    let mut is_regex = use_signal(|| false);
+    let mut match_case = use_signal(|| false);
+    let mut request: Signal<Option<SearchRequest>> = use_signal(|| None);
+    let mut results: Signal<Vec<FileHits>> = use_signal(Vec::new);
+    let mut progress = use_signal(Progress::default);
+    let mut error: Signal<Option<String>> = use_signal(|| None);
+
+    // A new request, or cancelling, drops the receiver, which stops the walk at the next entry
+    let mut searching = use_resource(move || async move {
+        results.write().clear();
+        progress.set(Progress::default());
+        let Some(request) = request() else {
+            return;
+        };
+        let regex = match request.regex() {
+            Ok(regex) => regex,
+            Err(err) => {
+                error.set(Some(err.to_string()));
+                return;
+            }
+        };
+        let (file_system, roots) = {
+            let reader = explorer.peek();
+            (reader.file_system().clone(), reader.roots().to_vec())
+        };
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(16);
+        tokio::task::spawn_blocking(move || {
+            let mut progress = Progress::default();
+            let mut hit_count = 0;
+            walk(&file_system, &request.root, MAX_DEPTH, &roots, |entry| {
+                if sender.is_closed() {
+                    return false;
+                }
+                if entry.kind == FileSystemType::Directory {
+                    return true;
+                }
+                // What is read is what the entry resolves to, so a symlink can not lead outside the roots
+                let path = file_system
+                    .absolute(&entry.path)
+                    .unwrap_or_else(|_| entry.path.clone());
+                if !is_within_roots(&roots, &path) {
+                    progress.skipped += 1;
+                    return true;
+                }
+                let hits = match search_file(&file_system, &path, &regex) {
+                    Ok(Some(hits)) => hits,
+                    Ok(None) | Err(_) => {
+                        progress.skipped += 1;
+                        return true;
+                    }
+                };
+                progress.searched += 1;
+                if !hits.is_empty() {
+                    hit_count += hits.len();
+                    let found = SearchEvent::Found(FileHits { entry, hits });
+                    if sender.blocking_send(found).is_err() {
+                        return false;
+                    }
+                }
+                let checked = progress.searched + progress.skipped;
+                if checked % PROGRESS_INTERVAL == 0
+                    && sender
+                        .blocking_send(SearchEvent::Progress(progress))
+                        .is_err()
+                {
+                    return false;
+                }
+                hit_count < MAX_HITS
+            });
+            let _ = sender.blocking_send(SearchEvent::Progress(progress));
+        });
+        while let Some(event) = receiver.recv().await {
+            match event {
+                SearchEvent::Progress(update) => progress.set(update),
+                SearchEvent::Found(file_hits) => results.write().push(file_hits),
+            }
+        }
+    });
+
+    let mut submit = move || {
+        error.set(None);
+        let reader = explorer.read();
+        if text.read().is_empty() || reader.is_listing_roots() {
+            return;
+        }
+        let root = reader.current().to_path_buf();
+        drop(reader);
+        request.set(Some(SearchRequest {
+            root,
+            text: text(),
+            is_regex: is_regex(),
+            match_case: match_case(),
+        }));
+    };
+    let mut toggle = move |entry: &WalkEntry| {
+        let mut writer = explorer.write();
+        // The walk reports files by the path they were found at, the selection holds what they resolve to
+        let path = writer
+            .file_system()
+            .absolute(&entry.path)
+            .unwrap_or_else(|_| entry.path.clone());
+        if writer.is_selected(&path) {
+            writer.deselect(&path);
+            return;
+        }
+        let selected = writer
+            .entry_at(&path)
+            .is_some_and(|selected| writer.select(&selected));
+        if selected {
+            writer.is_selecting = true;
+        } else {
+            error.set(Some(format!("{} can not be selected", entry.relative)));
+        }
+    };
+
+    let hit_count: usize = results.read().iter().map(|file| file.hits.len()).sum();
+    let file_count = results.read().len();
+    let Progress { searched, skipped } = progress();
+    let state = *searching.state().read();
+    let status = match (&*request.read(), state) {
+        (None, _) => "Press Enter to search the files below this directory".to_owned(),
+        (Some(_), UseResourceState::Pending) => {
+            format!("Searching… {searched} files searched, {hit_count} matches")
+        }
+        (Some(_), UseResourceState::Stopped) => {
+            format!("Stopped after {searched} files, {hit_count} matches in {file_count} files")
+        }
+        (Some(_), _) if hit_count >= MAX_HITS => {
+            format!("The first {hit_count} matches, in {file_count} files")
+        }
+        (Some(_), _) => format!(
+            "{hit_count} matches in {file_count} files, {searched} searched, {skipped} skipped"
+        ),
+    };
+    let reader = explorer.read();
+    rsx! {
+        div { class: "fp-content-search",
+            div { class: "fp-row",
+                i { class: "material-icons", "find_in_page" }
+                input {
+                    class: "fp-input",
+                    r#type: "text",
+                    spellcheck: false,
+                    placeholder: "Text the files contain",
+                    value: "{text}",
+                    onmounted: move |event| async move {
+                        let _ = event.set_focus(true).await;
+                    },
+                    oninput: move |event| text.set(event.value()),
+                    onkeydown: move |event| match event.key() {
+                        Key::Enter => submit(),
+                        Key::Escape => on_close.call(()),
+                        _ => {}
+                    },
+                }
+                label { class: "fp-row",
+                    input {
+                        r#type: "checkbox",
+                        checked: is_regex(),
+                        onchange: move |event| is_regex.set(event.checked()),
+                    }
+                    "Regex"
+                }
+                label { class: "fp-row",
+                    input {
+                        r#type: "checkbox",
+                        checked: match_case(),
+                        onchange: move |event| match_case.set(event.checked()),
+                    }
+                    "Match case"
+                }
+                i {
+                    class: "material-icons",
+                    title: "Close",
+                    onclick: move |_| on_close.call(()),
+                    "close"
+                }
+            }
+            div { class: "fp-row fp-quick-open-status",
+                span { "{status}" }
+                if request.read().is_some() && state == UseResourceState::Pending {
+                    button { onclick: move |_| searching.cancel(), "Stop" }
+                }
+            }
+            if let Some(error) = error() {
+                div { class: "fp-error", "{error}" }
+            }
+            div { class: "fp-quick-open-results",
+                for file in results() {
+                    div { key: "{file.entry.relative}", class: "fp-search-file",
+                        div {
+                            class: "fp-row fp-quick-open-result",
+                            title: "Add to the selection",
+                            onclick: {
+                                let entry = file.entry.clone();
+                                move |_| toggle(&entry)
+                            },
+                            i { class: "material-icons",
+                                if is_selected(&reader, &file.entry) {
+                                    "check_box"
+                                } else {
+                                    "check_box_outline_blank"
+                                }
+                            }
+                            span { class: "fp-search-path", "{file.entry.relative}" }
+                        }
+                        for hit in file.hits {
+                            div {
+                                class: "fp-row fp-search-hit",
+                                title: "Add to the selection",
+                                onclick: {
+                                    let entry = file.entry.clone();
+                                    move |_| toggle(&entry)
+                                },
+                                span { class: "fp-line-number", "{hit.line}" }
+                                span { class: "fp-search-snippet",
+                                    Highlighted { text: hit.snippet, ranges: hit.highlights }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn is_selected(explorer: &FileExplorer, entry: &WalkEntry) -> bool {
+    let path = explorer
+        .file_system()
+        .absolute(&entry.path)
+        .unwrap_or_else(|_| entry.path.clone());
+    explorer.is_selected(path)
+}
+
+/// The lines of the file that match. None if it is skipped, as binary or too large.
+fn search_file(
+    file_system: &FileSystem,
+    path: &Path,
+    regex: &Regex,
+) -> io::Result<Option<Vec<Hit>>> {
+    let metadata = file_system.metadata(path)?;
+    if metadata.size.is_none_or(|size| size > MAX_FILE_SIZE) {
+        return Ok(None);
+    }
+    let data = file_system.read_start(path, MAX_FILE_SIZE as usize)?;
+    if data[..data.len().min(BINARY_CHECK_LENGTH)].contains(&0) {
+        return Ok(None);
+    }
+    let text = String::from_utf8_lossy(&data);
+    let hits = text
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            snippet(line, regex).map(|(snippet, highlights)| Hit {
+                line: index + 1,
+                snippet,
+                highlights,
+            })
+        })
+        .take(MAX_HITS_PER_FILE)
+        .collect();
+    Ok(Some(hits))
+}
+
+/// The part of `line` around its first match, with the matches in it. None if it does not match.
+fn snippet(line: &str, regex: &Regex) -> Option<(String, Vec<Range<usize>>)> {
+    let first = regex.find(line)?;
+    let mut start = first.start().saturating_sub(SNIPPET_BEFORE);
+    while !line.is_char_boundary(start) {
+        start -= 1;
+    }
+    let mut end = (start + SNIPPET_LENGTH).min(line.len());
+    while !line.is_char_boundary(end) {
+        end += 1;
+    }
+    let prefix = if start > 0 { "…" } else { "" };
+    let suffix = if end < line.len() { "…" } else { "" };
+    let highlights = regex
+        .find_iter(line)
+        .map(|found| found.range())
+        .filter(|range| !range.is_empty() && range.start >= start && range.end <= end)
+        .map(|range| range.start - start + prefix.len()..range.end - start + prefix.len())
+        .collect();
+    let snippet = format!("{prefix}{}{suffix}", &line[start..end]);
+    Some((snippet, highlights))
+}
+
+#[cfg(test)]
+// Ranges are what is highlighted, a single one is meant
+#[allow(clippy::single_range_in_vec_init)]
+mod tests {
+    use super::*;
+
+    fn regex(pattern: &str) -> Regex {
+        Regex::new(pattern).unwrap()
+    }
+
+    #[test]
+    fn short_lines_are_shown_whole() {
+        let (snippet, highlights) = snippet("let answer = 42;", &regex("answer")).unwrap();
+        assert_eq!(snippet, "let answer = 42;");
+        assert_eq!(highlights, vec![4..10]);
+    }
+
+    #[test]
+    fn lines_without_a_match_have_no_snippet() {
+        assert_eq!(snippet("nothing here", &regex("answer")), None);
+    }
+
+    #[test]
+    fn every_match_in_the_snippet_is_highlighted() {
+        let (_, highlights) = snippet("a1 b22 c333", &regex(r"\d+")).unwrap();
+        assert_eq!(highlights, vec![1..2, 4..6, 8..11]);
+    }
+
+    #[test]
+    fn empty_matches_are_not_highlighted() {
+        let (snippet, highlights) = snippet("abc", &regex("x*")).unwrap();
+        assert_eq!(snippet, "abc");
+        assert!(highlights.is_empty());
+    }
+
+    #[test]
+    fn long_lines_are_cut_around_the_first_match() {
+        let line = format!("{}needle{}", "a".repeat(100), "b".repeat(300));
+        let (snippet, highlights) = snippet(&line, &regex("needle")).unwrap();
+        let prefix = "…".len();
+        assert!(snippet.starts_with('…') && snippet.ends_with('…'));
+        assert_eq!(snippet.len(), prefix + SNIPPET_LENGTH + "…".len());
+        assert_eq!(
+            highlights,
+            vec![prefix + SNIPPET_BEFORE..prefix + SNIPPET_BEFORE + 6]
+        );
+        assert_eq!(&snippet[highlights[0].clone()], "needle");
+    }
+
+    #[test]
+    fn matches_cut_off_are_not_highlighted() {
+        let line = format!("needle{}needle", "a".repeat(SNIPPET_LENGTH));
+        let (_, highlights) = snippet(&line, &regex("needle")).unwrap();
+        assert_eq!(highlights, vec![0..6]);
+    }
+
+    #[test]
+    fn cuts_fall_on_char_boundaries() {
+        let line = format!("{}needle{}", "é".repeat(100), "ü".repeat(200));
+        let (snippet, highlights) = snippet(&line, &regex("needle")).unwrap();
+        assert_eq!(&snippet[highlights[0].clone()], "needle");
+    }
+}
@@ -0,0 +1,314 @@
+use std::{cell::RefCell, collections::HashMap, ops::Range, time::SystemTime};
+
+use dioxus::prelude::*;
+
+use crate::{ExplorerEntry, FileExplorer, SortBy, SortOptions, SortOrder, filter::Highlighted};
+
+/// A column of the details view, with the sort its header applies and its initial width in pixels.
+struct Column {
+    label: &'static str,
+    sort: Option<SortBy>,
+    width: f64,
+}
+
+const COLUMNS: [Column; 6] = [
+    Column {
+        label: "Name",
+        sort: Some(SortBy::Name),
+        width: 280.0,
+    },
+    Column {
+        label: "Size",
+        sort: Some(SortBy::Size),
+        width: 90.0,
+    },
+    Column {
+        label: "Modified",
+        sort: Some(SortBy::Modified),
+        width: 150.0,
+    },
+    Column {
+        label: "Type",
+        sort: Some(SortBy::Type),
+        width: 160.0,
+    },
+    Column {
+        label: "Permissions",
+        sort: None,
+        width: 110.0,
+    },
+    Column {
+        label: "Owner",
+        sort: None,
+        width: 100.0,
+    },
+];
+
+const MIN_COLUMN_WIDTH: f64 = 40.0;
+
+/// A column being resized: which, and where the drag started.
+#[derive(Clone, Copy)]
+struct Resize {
+    column: usize,
+    start_x: f64,
+    start_width: f64,
+}
+
+/// The entries as a table with their size, modification time, type, permissions and owner. Headers sort by their
+/// column, a second click reverses the order, and the edge of a header can be dragged to resize its column.
+#[component]
+pub(crate) fn DetailsView(explorer: Signal<FileExplorer>) -> Element {
+    let mut widths = use_signal(|| COLUMNS.map(|column| column.width));
+    let mut resize: Signal<Option<Resize>> = use_signal(|| None);
+    let reader = explorer.read();
+    let sort = reader.sort();
+    rsx! {
+        div {
+            class: "fp-details",
+            onmousemove: move |event| {
+                if let Some(Resize { column, start_x, start_width }) = resize() {
+                    let x = event.client_coordinates().x;
+                    widths.write()[column] = (start_width + x - start_x).max(MIN_COLUMN_WIDTH);
+                }
+            },
+            onmouseup: move |_| resize.set(None),
+            onmouseleave: move |_| resize.set(None),
+            table {
+                colgroup {
+                    for width in widths() {
+                        col { style: "width: {width}px" }
+                    }
+                }
+                thead {
+                    tr {
+                        for (index , column) in COLUMNS.iter().enumerate() {
+                            th {
+                                class: if column.sort.is_some() { "fp-sortable" },
+                                onclick: move |_| {
+                                    let Some(by) = COLUMNS[index].sort else {
+                                        return;
+                                    };
+                                    let order = if by == sort.by { sort.order.reversed() } else { sort.order };
+                                    explorer.write().set_sort(SortOptions { by, order, ..sort });
+                                },
+                                "{column.label}"
+                                if column.sort == Some(sort.by) {
+                                    i { class: "material-icons fp-sort-indicator",
+                                        match sort.order {
+                                            SortOrder::Ascending => "arrow_drop_up",
+                                            SortOrder::Descending => "arrow_drop_down",
+                                        }
+                                    }
+                                }
+                                span {
+                                    class: "fp-column-resizer",
+                                    onclick: move |event| event.stop_propagation(),
+                                    onmousedown: move |event| {
+                                        event.stop_propagation();
+                                        resize
+                                            .set(
+                                                Some(Resize {
+                                                    column: index,
+                                                    start_x: event.client_coordinates().x,
+                                                    start_width: widths()[index],
+                                                }),
+                                            );
+                                    },
+                                }
+                            }
+                        }
+                    }
+                }
+                tbody {
+                    for (entry , highlights) in reader.visible_entries() {
+                        DetailsRow {
+                            key: "{entry.path().display()}",
+                            explorer,
+                            is_selectable: reader.is_selectable(&entry),
+                            is_selected: reader.is_selected(entry.path()),
+                            entry,
+                            highlights,
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[component]
+fn DetailsRow(
+    explorer: Signal<FileExplorer>,
+    entry: ExplorerEntry,
+    is_selectable: bool,
+    is_selected: bool,
+    /// The ranges of the name the filter matched
+    highlights: Vec<Range<usize>>,
+) -> Element {
+    let class = match (is_selected, entry.is_dir() || is_selectable) {
+        (true, _) => "fp-details-row bg-blue-500",
+        (false, true) => "fp-details-row",
+        (false, false) => "fp-details-row deactivated",
+    };
+    let size = match entry.size() {
+        Some(size) => format_size(size),
+        None => "—".to_owned(),
+    };
+    let modified = entry.modified().map(format_modified).unwrap_or_default();
+    let kind = match entry.mime_type() {
+        _ if entry.is_dir() => "Folder",
+        Some(mime_type) => mime_type,
+        None => "Unknown",
+    };
+    let permissions = entry
+        .permissions()
+        .map(|permissions| format_permissions(permissions, entry.is_dir()))
+        .unwrap_or_default();
+    let owner = entry.owner().map(owner_name).unwrap_or_default();
+    let icon = if entry.is_dir() {
+        "folder"
+    } else {
+        "description"
+    };
+    let name = entry.name().to_owned();
+    rsx! {
+        tr {
+            class,
+            onclick: move |_| {
+                let mut writer = explorer.write();
+                let selects_directories = writer.is_selecting
+                    && writer.selection_kind().allows_directories();
+                if entry.is_dir() && !selects_directories {
+                    writer.navigate(entry.path());
+                    return;
+                }
+                writer.focused = Some(entry.path().to_path_buf());
+                if writer.is_selecting {
+                    writer.toggle(&entry);
+                }
+            },
+            td {
+                div { class: "fp-row",
+                    i { class: "material-icons", "{icon}" }
+                    span {
+                        Highlighted { text: name, ranges: highlights }
+                    }
+                }
+            }
+            td { class: "fp-numeric", "{size}" }
+            td { "{modified}" }
+            td { "{kind}" }
+            td { class: "fp-monospace", "{permissions}" }
+            td { "{owner}" }
+        }
+    }
+}
+
+/// Formats a size with SI units, e.g. `1.5 MB`.
+pub(crate) fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["kB", "MB", "GB", "TB", "PB"];
+    if bytes < 1000 {
+        return if bytes == 1 {
+            "1 byte".to_owned()
+        } else {
+            format!("{bytes} bytes")
+        };
+    }
+    let mut size = bytes as f64 / 1000.0;
+    let mut unit = 0;
+    // Compared after rounding to one decimal, so 999 999 bytes show as 1.0 MB, not 1000.0 kB
+    while size >= 999.95 && unit < UNITS.len() - 1 {
+        size /= 1000.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+/// Formats a time in the system's time zone, e.g. `2024-05-01 14:03`.
+pub(crate) fn format_modified(time: SystemTime) -> String {
+    match jiff::Timestamp::try_from(time) {
+        Ok(timestamp) => timestamp
+            .to_zoned(jiff::tz::TimeZone::system())
+            .strftime("%Y-%m-%d %H:%M")
+            .to_string(),
+        Err(_) => String::new(),
+    }
+}
+
+/// Formats permission bits like `ls -l`, e.g. `drwxr-xr-x`.
+pub(crate) fn format_permissions(mode: u32, is_dir: bool) -> String {
+    let mut formatted = String::with_capacity(10);
+    formatted.push(if is_dir { 'd' } else { '-' });
+    // The setuid, setgid and sticky bits show in the execute position of user, group and other
+    let special = [(0o4000, 's'), (0o2000, 's'), (0o1000, 't')];
+    for (index, (special_bit, special_char)) in special.into_iter().enumerate() {
+        let bits = (mode >> (6 - index * 3)) & 0o7;
+        formatted.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        formatted.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        formatted.push(match (bits & 0o1 != 0, mode & special_bit != 0) {
+            (true, true) => special_char,
+            (false, true) => special_char.to_ascii_uppercase(),
+            (true, false) => 'x',
+            (false, false) => '-',
+        });
+    }
+    formatted
+}
+
+/// The name of the user with `uid`, or the id if there is none. Looked up once per user.
+pub(crate) fn owner_name(uid: u32) -> String {
+    thread_local! {
+        static NAMES: RefCell<HashMap<u32, String>> = RefCell::new(HashMap::new());
+    }
+    NAMES.with_borrow_mut(|names| {
+        names
+            .entry(uid)
+            .or_insert_with(|| {
+                #[cfg(unix)]
+                if let Some(user) = uzers::get_user_by_uid(uid) {
+                    return user.name().to_string_lossy().into_owned();
+                }
+                uid.to_string()
+            })
+            .clone()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_use_decimal_units() {
+        assert_eq!(format_size(0), "0 bytes");
+        assert_eq!(format_size(1), "1 byte");
+        assert_eq!(format_size(999), "999 bytes");
+        assert_eq!(format_size(1000), "1.0 kB");
+        assert_eq!(format_size(1023), "1.0 kB");
+        assert_eq!(format_size(1024), "1.0 kB");
+        assert_eq!(format_size(1_560_000), "1.6 MB");
+        assert_eq!(format_size(u64::MAX), "18446.7 PB");
+    }
+
+    #[test]
+    fn sizes_that_round_up_use_the_next_unit() {
+        assert_eq!(format_size(999_940), "999.9 kB");
+        assert_eq!(format_size(999_999), "1.0 MB");
+    }
+
+    #[test]
+    fn permissions_format_like_ls() {
+        assert_eq!(format_permissions(0o755, true), "drwxr-xr-x");
+        assert_eq!(format_permissions(0o640, false), "-rw-r-----");
+        assert_eq!(format_permissions(0o000, false), "----------");
+    }
+
+    #[test]
+    fn special_bits_show_in_the_execute_position() {
+        assert_eq!(format_permissions(0o4755, false), "-rwsr-xr-x");
+        assert_eq!(format_permissions(0o4644, false), "-rwSr--r--");
+        assert_eq!(format_permissions(0o2755, true), "drwxr-sr-x");
+        assert_eq!(format_permissions(0o1777, true), "drwxrwxrwt");
+        assert_eq!(format_permissions(0o1770, true), "drwxrwx--T");
+    }
+}
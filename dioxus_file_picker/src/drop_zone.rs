@@ -0,0 +1,356 @@
+use std::{fmt, path::PathBuf};
+
+use dioxus::{
+    html::{FileEngine, HasFileData},
+    logger::tracing::debug,
+    prelude::*,
+};
+
+use crate::{Accept, SelectionKind, VirtualPaths};
+
+/// Why a drop on a [FileDropZone] was rejected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DropError {
+    /// Nothing that was dropped is accepted by the zone. Holds the names of what was dropped.
+    NotAccepted(Vec<String>),
+    /// More than one entry was dropped on a zone that is not `multiple`. Holds how many.
+    TooMany(usize),
+}
+
+impl fmt::Display for DropError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DropError::NotAccepted(names) => {
+                write!(
+                    f,
+                    "None of the dropped entries are accepted: {}",
+                    names.join(", ")
+                )
+            }
+            DropError::TooMany(count) => write!(f, "Only one entry can be dropped, got {count}"),
+        }
+    }
+}
+
+impl std::error::Error for DropError {}
+
+/// An area files and folders can be dragged onto from the system's file manager. A drop is validated with the same
+/// `multiple`, `selection` and `accept` rules as a [crate::FilePickerLauncher] and passed to `on_drop` exactly as if
+/// it had been picked.
+///
+/// Dropped folders are expanded into the accepted files inside them, unless directories can be selected. On web they
+/// are always expanded, as there a [VirtualPaths] only holds files. The zone has the `fp-drop-zone` class, and
+/// `fp-drop-zone-active` while something is dragged over it.
+#[component]
+pub fn FileDropZone(
+    /// Can drop multiple
+    multiple: bool,
+    /// Whether files, directories or both can be dropped. Defaults to files.
+    #[props(default)]
+    selection: SelectionKind,
+    /// The files to accept, e.g. `".csv,image/*"`. Accepts everything if empty.
+    #[props(default, into)]
+    accept: Accept,
+    /// The callback to call with what was dropped, once validated.
+    on_drop: Callback<VirtualPaths, ()>,
+    /// The callback to call when a drop is rejected.
+    on_error: Option<Callback<DropError, ()>>,
+    children: Element,
+) -> Element {
+    // Entering a child fires dragenter before the parent's dragleave, so the nesting is counted
+    let mut drag_depth = use_signal(|| 0usize);
+    let submit = use_callback(move |dropped: Dropped| {
+        let accepted_count = dropped.accepted_count;
+        let result = if accepted_count == 0 {
+            Err(DropError::NotAccepted(dropped.rejected))
+        } else if !multiple && accepted_count > 1 {
+            Err(DropError::TooMany(accepted_count))
+        } else {
+            if !dropped.rejected.is_empty() {
+                debug!(
+                    "Ignoring dropped entries that are not accepted: {:?}",
+                    dropped.rejected
+                );
+            }
+            Ok(dropped.paths)
+        };
+        match result {
+            Ok(paths) => on_drop.call(paths),
+            Err(error) => match on_error {
+                Some(on_error) => on_error.call(error),
+                None => debug!("{error}"),
+            },
+        }
+    });
+    rsx! {
+        document::Link {
+            rel: "stylesheet",
+            href: asset!("/assets/integrated_file_picker.css"),
+        }
+        div {
+            class: if drag_depth() > 0 { "fp-drop-zone fp-drop-zone-active" } else { "fp-drop-zone" },
+            ondragenter: move |event| {
+                event.prevent_default();
+                drag_depth += 1;
+            },
+            // Without this the browser does not allow dropping
+            ondragover: move |event| event.prevent_default(),
+            ondragleave: move |_| {
+                let depth = drag_depth();
+                drag_depth.set(depth.saturating_sub(1));
+            },
+            ondrop: move |event| {
+                event.prevent_default();
+                drag_depth.set(0);
+                let accept = accept.clone();
+                // Web
+                #[cfg(target_arch = "wasm32")]
+                {
+                    // The dropped entries are only accessible while the event is dispatched
+                    let entries = web::dropped_entries(&event);
+                    spawn(async move {
+                        submit.call(web::collect(entries, &accept, selection).await);
+                    });
+                }
+                // Desktop/mobile
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    let paths: Vec<PathBuf> = event
+                        .files()
+                        .map(|file_engine| file_engine.files().into_iter().map(PathBuf::from).collect())
+                        .unwrap_or_default();
+                    spawn(async move {
+                        submit.call(native::collect(paths, &accept, selection).await);
+                    });
+                }
+            },
+            {children}
+        }
+    }
+}
+
+/// What was dropped, after expanding folders and filtering by the zone's rules.
+struct Dropped {
+    paths: VirtualPaths,
+    accepted_count: usize,
+    /// The names of the dropped entries that are not accepted
+    rejected: Vec<String>,
+}
+
+// Native
+//************************************************************************//
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use std::{
+        collections::HashSet,
+        path::{Path, PathBuf},
+    };
+
+    use dioxus::logger::tracing::debug;
+    use tokio::fs;
+
+    use super::Dropped;
+    use crate::{Accept, SelectionKind, VirtualPaths};
+
+    pub(super) async fn collect(
+        paths: Vec<PathBuf>,
+        accept: &Accept,
+        selection: SelectionKind,
+    ) -> Dropped {
+        let mut accepted = HashSet::new();
+        let mut rejected = Vec::new();
+        for path in paths {
+            let is_dir = fs::metadata(&path)
+                .await
+                .is_ok_and(|metadata| metadata.is_dir());
+            if is_dir && selection.allows_directories() {
+                accepted.insert(path);
+            } else if is_dir {
+                let before = accepted.len();
+                let (dir, dir_accept) = (path.clone(), accept.clone());
+                let files = tokio::task::spawn_blocking(move || expand_dir(&dir, &dir_accept))
+                    .await
+                    .unwrap_or_default();
+                accepted.extend(files);
+                if accepted.len() == before {
+                    rejected.push(display_name(&path));
+                }
+            } else if selection.allows_files() && accept.matches(&path) {
+                accepted.insert(path);
+            } else {
+                rejected.push(display_name(&path));
+            }
+        }
+        Dropped {
+            accepted_count: accepted.len(),
+            paths: VirtualPaths::native(accepted),
+            rejected,
+        }
+    }
+
+    /// How many entries below a dropped folder are looked at, so dropping e.g. a home directory does not walk the
+    /// whole disk.
+    const MAX_EXPANDED_ENTRIES: usize = 100_000;
+
+    /// The accepted files anywhere below `dir`. Symlinked directories are not followed, so links back up the tree can
+    /// not loop. Blocks on file io, so it is meant to run on a blocking thread.
+    fn expand_dir(dir: &Path, accept: &Accept) -> Vec<PathBuf> {
+        // Everything dropped counts, including hidden and ignored files
+        let walker = ignore::WalkBuilder::new(dir)
+            .standard_filters(false)
+            .follow_links(false)
+            .build();
+        let mut files = Vec::new();
+        for entry in walker.skip(1).take(MAX_EXPANDED_ENTRIES) {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(err) => {
+                    debug!("Skipping while expanding {}: {err}", dir.display());
+                    continue;
+                }
+            };
+            let is_file = match entry.file_type() {
+                Some(file_type) if file_type.is_symlink() => entry.path().is_file(),
+                Some(file_type) => file_type.is_file(),
+                None => false,
+            };
+            if is_file && accept.matches(entry.path()) {
+                files.push(entry.into_path());
+            }
+        }
+        files
+    }
+
+    fn display_name(path: &std::path::Path) -> String {
+        path.file_name()
+            .unwrap_or(path.as_os_str())
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+// Web
+//************************************************************************//
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use dioxus::prelude::*;
+    use wasm_bindgen::{JsCast, JsValue};
+    use wasm_bindgen_futures::JsFuture;
+    use web_sys::{FileSystemDirectoryEntry, FileSystemEntry, FileSystemFileEntry};
+
+    use super::Dropped;
+    use crate::{Accept, SelectionKind, VirtualPaths};
+
+    /// A dropped item, as an entry where the browser supports `webkitGetAsEntry` and as a plain file otherwise.
+    pub(super) enum DroppedEntry {
+        Entry(FileSystemEntry),
+        File(web_sys::File),
+    }
+
+    pub(super) fn dropped_entries(event: &Event<DragData>) -> Vec<DroppedEntry> {
+        let data = event.data();
+        let drag_event = data.downcast::<web_sys::DragEvent>().cloned().or_else(|| {
+            data.downcast::<web_sys::Event>()
+                .and_then(|event| event.dyn_ref::<web_sys::DragEvent>().cloned())
+        });
+        let Some(data_transfer) = drag_event.and_then(|event| event.data_transfer()) else {
+            return Vec::new();
+        };
+        let items = data_transfer.items();
+        (0..items.length())
+            .filter_map(|index| items.get(index))
+            .filter(|item| item.kind() == "file")
+            .filter_map(|item| match item.webkit_get_as_entry() {
+                Ok(Some(entry)) => Some(DroppedEntry::Entry(entry)),
+                _ => item.get_as_file().ok().flatten().map(DroppedEntry::File),
+            })
+            .collect()
+    }
+
+    pub(super) async fn collect(
+        entries: Vec<DroppedEntry>,
+        accept: &Accept,
+        selection: SelectionKind,
+    ) -> Dropped {
+        let mut accepted = Vec::new();
+        let mut rejected = Vec::new();
+        for entry in entries {
+            match entry {
+                DroppedEntry::Entry(entry) if entry.is_directory() => {
+                    let before = accepted.len();
+                    expand_dir(entry.clone().unchecked_into(), accept, &mut accepted).await;
+                    if accepted.len() == before {
+                        rejected.push(entry.name());
+                    }
+                }
+                DroppedEntry::Entry(entry) => {
+                    let name = entry.name();
+                    match file(&entry.unchecked_into()).await {
+                        Ok(file) if selection.allows_files() && accept.matches(&name) => {
+                            accepted.push((name, file))
+                        }
+                        _ => rejected.push(name),
+                    }
+                }
+                DroppedEntry::File(file) => {
+                    let name = file.name();
+                    if selection.allows_files() && accept.matches(&name) {
+                        accepted.push((name, file));
+                    } else {
+                        rejected.push(name);
+                    }
+                }
+            }
+        }
+        Dropped {
+            accepted_count: accepted.len(),
+            paths: VirtualPaths::web_files(accepted),
+            rejected,
+        }
+    }
+
+    /// Adds the accepted files anywhere below `dir`, with their path relative to the dropped folder's parent.
+    async fn expand_dir(
+        dir: FileSystemDirectoryEntry,
+        accept: &Accept,
+        accepted: &mut Vec<(String, web_sys::File)>,
+    ) {
+        let mut pending = vec![dir];
+        while let Some(dir) = pending.pop() {
+            let reader = dir.create_reader();
+            // Entries come in batches, an empty one marks the end
+            loop {
+                let promise = js_sys::Promise::new(&mut |resolve, reject| {
+                    let _ = reader.read_entries_with_callback_and_callback(&resolve, &reject);
+                });
+                let Ok(batch) = JsFuture::from(promise).await else {
+                    break;
+                };
+                let batch = js_sys::Array::from(&batch);
+                if batch.length() == 0 {
+                    break;
+                }
+                for entry in batch.iter() {
+                    let entry: FileSystemEntry = entry.unchecked_into();
+                    if entry.is_directory() {
+                        pending.push(entry.unchecked_into());
+                    } else if accept.matches(entry.name())
+                        && let Ok(file) = file(&entry.clone().unchecked_into()).await
+                    {
+                        let path = entry.full_path().trim_start_matches('/').to_owned();
+                        accepted.push((path, file));
+                    }
+                }
+            }
+        }
+    }
+
+    async fn file(entry: &FileSystemFileEntry) -> Result<web_sys::File, JsValue> {
+        let promise = js_sys::Promise::new(&mut |resolve, reject| {
+            entry.file_with_callback_and_callback(&resolve, &reject);
+        });
+        Ok(JsFuture::from(promise).await?.unchecked_into())
+    }
+}
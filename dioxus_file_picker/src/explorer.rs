@@ -0,0 +1,1285 @@
+use std::{
+    cmp::Ordering,
+    collections::HashSet,
+    env, io, mem,
+    ops::Range,
+    path::{Component, Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+use dioxus::{
+    logger::tracing::{debug, error},
+    prelude::*,
+};
+
+use crate::{
+    Accept, DirEntry, FileSystem, FileSystemType, SelectionKind, SortBy, SortOptions, VirtualPaths,
+    WatchEvent,
+    filter::NameFilter,
+    session::{Layout, PickerSession, ViewSettings},
+    sort::natural_cmp,
+};
+
+/// Options for a [FileExplorer].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExplorerOptions {
+    /// The directory to start in. If None, defaults to current directory.
+    pub open_at: Option<PathBuf>,
+    /// The files to accept. Files not matching are listed but cannot be selected.
+    pub accept: Accept,
+    /// Whether files, directories or both can be selected.
+    pub selection: SelectionKind,
+    /// Can select multiple
+    pub multiple: bool,
+    /// What is browsed. Defaults to the local disk.
+    pub file_system: FileSystem,
+    /// If not empty, the only directories that can be browsed. Each is a top level: the explorer never goes above
+    /// it, entries resolving outside all of them are hidden and only paths inside them can be selected. With more
+    /// than one root, going up from a root lists the roots.
+    pub roots: Vec<PathBuf>,
+    /// How the entries are ordered.
+    pub sort: SortOptions,
+}
+
+/// The navigation, listing and selection logic of the integrated file picker, without any ui. Build a custom picker
+/// on top of it with [use_file_explorer], the bundled picker is just one such ui.
+///
+/// Navigation keeps a browser-like history: [FileExplorer::navigate] and [FileExplorer::go_up] drop everything
+/// forward of the current directory, [FileExplorer::go_back] and [FileExplorer::go_forward] move within it. Every
+/// navigation lists the new directory; if that fails the error is kept in [FileExplorer::error].
+///
+/// Listing runs in the background while the explorer is in a [use_file_explorer] signal: a navigation clears the
+/// entries at once, they stream in while [FileExplorer::is_loading] and a listing still in flight is cancelled by the
+/// next navigation, so the entries of another directory are never shown. The current directory is watched, if the
+/// file system supports it, and entries created, removed or renamed since are applied as they happen.
+///
+/// With [ExplorerOptions::roots], [FileExplorer::current] is empty while the roots are listed, see
+/// [FileExplorer::is_listing_roots].
+///
+/// We don't use any fancy signals or memoization here - Dioxus is so fast that even a file explorer can be done with a
+/// single signal.
+#[derive(Clone, Debug)]
+pub struct FileExplorer {
+    /// Always canonicalized, and kept in the order of [FileExplorer::sort]
+    entries: Vec<ExplorerEntry>,
+    sort: SortOptions,
+    is_root: bool,
+    history: Vec<PathBuf>,
+    history_position: usize,
+    selection: HashSet<PathBuf>,
+    /// If the bundled ui is in select mode
+    pub(crate) is_selecting: bool,
+    /// How the bundled ui lays out the entries
+    pub(crate) layout: Layout,
+    /// The entry the bundled ui previews
+    pub(crate) focused: Option<PathBuf>,
+    /// Which entries of the current directory the bundled ui shows. Cleared when the directory changes
+    pub(crate) filter: NameFilter,
+    /// If Some, an error occurred with the current operation
+    error: Option<String>,
+    accept: Accept,
+    selection_kind: SelectionKind,
+    multiple: bool,
+    file_system: FileSystem,
+    /// Absolute, empty if unrestricted
+    roots: Vec<PathBuf>,
+    /// Bumped on every listing, so batches of a listing that was replaced are dropped
+    generation: u64,
+    is_loading: bool,
+    /// The names of the entries a watch event came in for during the current listing. They are up to date, so the
+    /// listing skips them
+    changed_while_loading: HashSet<String>,
+}
+
+/// Creates a [FileExplorer] in a signal, so the ui reading it re-renders on every navigation and selection. `options`
+/// are only used on the first render.
+///
+/// ```rust,ignore
+/// let mut explorer = use_file_explorer(ExplorerOptions::default());
+/// rsx! {
+///     button { onclick: move |_| explorer.write().go_up(), "Up" }
+///     for entry in explorer.read().entries().to_vec() {
+///         div {
+///             onclick: move |_| {
+///                 if entry.is_dir() {
+///                     explorer.write().navigate(entry.path());
+///                 } else {
+///                     explorer.write().toggle(&entry);
+///                 }
+///             },
+///             "{entry.name()}"
+///         }
+///     }
+/// }
+/// ```
+pub fn use_file_explorer(options: ExplorerOptions) -> Signal<FileExplorer> {
+    let explorer = use_signal(|| FileExplorer::new(options));
+    use_listing(explorer);
+    explorer
+}
+
+/// Entries are sent to the ui in batches of this many, or of what was read in [BATCH_INTERVAL] on slow file systems
+const BATCH_SIZE: usize = 256;
+const BATCH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A listing the explorer waits for.
+#[derive(Clone, Debug, PartialEq)]
+struct ListingRequest {
+    path: PathBuf,
+    generation: u64,
+}
+
+/// What the listing thread sends back.
+enum ListingEvent {
+    Entries(Vec<DirEntry>),
+    Failed(io::Error),
+    Done,
+}
+
+/// Lists the directories `explorer` navigates to off the ui thread and watches the current one. A new request drops
+/// the task of the previous one, which closes its channel and so stops its thread at the next batch.
+pub(crate) fn use_listing(mut explorer: Signal<FileExplorer>) {
+    let watched = use_memo(move || explorer.read().watch_target());
+    use_resource(move || async move {
+        let Some(dir) = watched() else {
+            return;
+        };
+        let file_system = explorer.peek().file_system.clone();
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        // Stops watching when dropped, with this task
+        let _watcher = match file_system.watch(&dir, Box::new(move |event| _ = sender.send(event)))
+        {
+            Ok(watcher) => watcher,
+            Err(err) => {
+                debug!("Not watching {}: {err}", dir.display());
+                return;
+            }
+        };
+        while let Some(event) = receiver.recv().await {
+            // A download or copy sends a burst of events, they are applied at once
+            let mut events = vec![event];
+            while let Ok(event) = receiver.try_recv() {
+                events.push(event);
+            }
+            explorer.write().apply(&dir, events);
+        }
+    });
+
+    let request = use_memo(move || explorer.read().listing_request());
+    use_resource(move || async move {
+        let Some(ListingRequest { path, generation }) = request() else {
+            return;
+        };
+        let file_system = explorer.peek().file_system.clone();
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(4);
+        tokio::task::spawn_blocking(move || {
+            let entries = match file_system.read_dir(&path) {
+                Ok(entries) => entries,
+                Err(err) => {
+                    let _ = sender.blocking_send(ListingEvent::Failed(err));
+                    return;
+                }
+            };
+            let mut batch = Vec::new();
+            let mut batch_started = Instant::now();
+            for entry in entries {
+                match entry {
+                    Ok(entry) => batch.push(entry),
+                    Err(err) => error!("Could not get typed path for entity: {err}"),
+                }
+                if batch.len() >= BATCH_SIZE || batch_started.elapsed() >= BATCH_INTERVAL {
+                    let batch = mem::take(&mut batch);
+                    if sender.blocking_send(ListingEvent::Entries(batch)).is_err() {
+                        // Cancelled
+                        return;
+                    }
+                    batch_started = Instant::now();
+                }
+            }
+            let _ = sender.blocking_send(ListingEvent::Entries(batch));
+            let _ = sender.blocking_send(ListingEvent::Done);
+        });
+        while let Some(event) = receiver.recv().await {
+            explorer.write().receive(generation, event);
+        }
+    });
+}
+
+impl FileExplorer {
+    /// Opens at `options.open_at`, or the current directory, and lists it. With roots, opens at the top level instead
+    /// if that is outside them.
+    pub fn new(options: ExplorerOptions) -> Self {
+        let path = match options.open_at.clone() {
+            Some(path) => path,
+            None => env::current_dir()
+                .expect("Failed to retrieve current working directory using env::current_dir()"),
+        };
+        let current = options
+            .file_system
+            .absolute(&path)
+            .unwrap_or_else(|_| panic!("Could not get absolute path of {}", path.display()));
+        let mut explorer = Self::with_history(vec![current], 0, options);
+        if !explorer.is_allowed(explorer.current()) {
+            explorer.history = vec![explorer.top_level()];
+        }
+        explorer.reload();
+        explorer
+    }
+
+    fn with_history(
+        history: Vec<PathBuf>,
+        history_position: usize,
+        options: ExplorerOptions,
+    ) -> Self {
+        // Kept even if they can't be resolved, an unreadable root must not lift the restriction
+        let roots = options
+            .roots
+            .iter()
+            .map(|root| {
+                options
+                    .file_system
+                    .absolute(root)
+                    .unwrap_or_else(|_| root.clone())
+            })
+            .collect();
+        Self {
+            entries: vec![],
+            sort: options.sort,
+            is_root: false,
+            history,
+            history_position,
+            selection: HashSet::new(),
+            is_selecting: false,
+            layout: Layout::default(),
+            focused: None,
+            filter: NameFilter::default(),
+            error: None,
+            accept: options.accept,
+            selection_kind: options.selection,
+            multiple: options.multiple,
+            file_system: options.file_system,
+            roots,
+            generation: 0,
+            is_loading: false,
+            changed_while_loading: HashSet::new(),
+        }
+    }
+
+    /// Continues where `session` left off. None if none of its directories exist anymore.
+    pub(crate) fn restore(session: PickerSession, options: ExplorerOptions) -> Option<Self> {
+        let current = session
+            .history
+            .len()
+            .checked_sub(session.history_position + 1)?;
+        let mut explorer = Self::with_history(Vec::new(), 0, options);
+        // Directories removed since, or outside the roots, are dropped. If it was the current one, the picker opens at
+        // the one before
+        let mut history = Vec::with_capacity(session.history.len());
+        let mut history_position = 0;
+        for (index, path) in session.history.into_iter().enumerate() {
+            let is_dir = explorer
+                .file_system
+                .metadata(&path)
+                .is_ok_and(|metadata| metadata.kind == FileSystemType::Directory);
+            let is_roots_listing = explorer.roots.len() > 1 && path.as_os_str().is_empty();
+            if (is_dir && explorer.is_allowed(&path)) || is_roots_listing {
+                if index > current {
+                    history_position += 1;
+                }
+                history.push(path);
+            }
+        }
+        if history.is_empty() {
+            return None;
+        }
+        explorer.history_position = history_position.min(history.len() - 1);
+        explorer.history = history;
+        explorer.is_selecting = session.view.is_selecting;
+        explorer.sort = session.view.sort;
+        explorer.layout = session.view.layout;
+        explorer.reload();
+        Some(explorer)
+    }
+
+    /// What is stored for a picker with a persistence key.
+    pub(crate) fn session(&self) -> PickerSession {
+        PickerSession {
+            history: self.history.clone(),
+            history_position: self.history_position,
+            view: ViewSettings {
+                is_selecting: self.is_selecting,
+                sort: self.sort,
+                layout: self.layout,
+            },
+        }
+    }
+
+    // Listing
+    //************************************************************************//
+
+    /// The directory being shown.
+    pub fn current(&self) -> &Path {
+        &self.history[self.history.len() - self.history_position - 1]
+    }
+
+    /// The entries of the current directory.
+    pub fn entries(&self) -> &[ExplorerEntry] {
+        &self.entries
+    }
+
+    /// The entries [FileExplorer::filter] lets through, in order, with the ranges of their names that matched.
+    pub(crate) fn visible_entries(&self) -> Vec<(ExplorerEntry, Vec<Range<usize>>)> {
+        self.entries
+            .iter()
+            .filter_map(|entry| Some((entry.clone(), self.filter.matches(&entry.name)?)))
+            .collect()
+    }
+
+    /// The listed entry at [FileExplorer::focused], if any.
+    pub(crate) fn focused_entry(&self) -> Option<&ExplorerEntry> {
+        let focused = self.focused.as_deref()?;
+        self.entries.iter().find(|entry| entry.path == focused)
+    }
+
+    /// The error of the last navigation or reload, if it failed.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Clears [FileExplorer::error].
+    pub fn dismiss_error(&mut self) {
+        self.error = None;
+    }
+
+    /// Returns true while the current directory is being listed. [FileExplorer::entries] holds what was listed so far.
+    pub fn is_loading(&self) -> bool {
+        self.is_loading
+    }
+
+    /// Lists the current directory again.
+    pub fn reload(&mut self) {
+        // Whatever is in flight is for another directory, or outdated
+        self.generation += 1;
+        self.entries.clear();
+        self.changed_while_loading.clear();
+        self.error = None;
+        if self.is_listing_roots() {
+            self.entries = self
+                .roots
+                .iter()
+                .map(|root| self.root_entry(root))
+                .collect();
+            self.is_root = true;
+            self.is_loading = false;
+            return;
+        }
+        let current_path = self.current();
+        self.is_root = match self.roots.len() {
+            0 => self.file_system.parent(current_path).is_none(),
+            // Going up from a root lists the roots
+            1 => self.roots.iter().any(|root| root == current_path),
+            _ => false,
+        };
+        self.is_loading = true;
+    }
+
+    fn listing_request(&self) -> Option<ListingRequest> {
+        self.is_loading.then(|| ListingRequest {
+            path: self.current().to_path_buf(),
+            generation: self.generation,
+        })
+    }
+
+    /// Adds what the listing of `generation` sent, unless the explorer has moved on since.
+    fn receive(&mut self, generation: u64, event: ListingEvent) {
+        if generation != self.generation || !self.is_loading {
+            return;
+        }
+        match event {
+            ListingEvent::Entries(entries) => {
+                for entity in entries {
+                    if self.changed_while_loading.contains(&entity.name) {
+                        continue;
+                    }
+                    // A symlink out of the roots
+                    if !self.is_allowed(&entity.path) {
+                        debug!("Hiding {}, it is outside the roots", entity.path.display());
+                        continue;
+                    }
+                    let entry = self.entry(entity);
+                    self.entries.push(entry);
+                }
+                self.sort_entries();
+            }
+            ListingEvent::Failed(err) => {
+                self.error = Some(format!("An error occurred: {err:?}"));
+                self.is_loading = false;
+                self.changed_while_loading.clear();
+            }
+            ListingEvent::Done => {
+                self.is_loading = false;
+                self.changed_while_loading.clear();
+            }
+        }
+    }
+
+    fn entry(&self, entity: DirEntry) -> ExplorerEntry {
+        let DirEntry {
+            path,
+            name,
+            metadata,
+        } = entity;
+        // todo check permissions?
+        let is_accepted = match metadata.kind {
+            FileSystemType::File => self.accept.matches(&path),
+            FileSystemType::Directory => true,
+        };
+        ExplorerEntry {
+            kind: metadata.kind,
+            path,
+            name,
+            is_accepted,
+            size: metadata.size,
+            modified: metadata.modified,
+            permissions: metadata.permissions,
+            owner: metadata.owner,
+        }
+    }
+
+    fn root_entry(&self, root: &Path) -> ExplorerEntry {
+        let name = match root.file_name() {
+            Some(name) => name.to_string_lossy().into_owned(),
+            None => root.display().to_string(),
+        };
+        let metadata = self.file_system.metadata(root).ok();
+        ExplorerEntry {
+            kind: FileSystemType::Directory,
+            path: root.to_path_buf(),
+            name,
+            is_accepted: true,
+            size: None,
+            modified: metadata.as_ref().and_then(|metadata| metadata.modified),
+            permissions: metadata.as_ref().and_then(|metadata| metadata.permissions),
+            owner: metadata.and_then(|metadata| metadata.owner),
+        }
+    }
+
+    // Roots
+    //************************************************************************//
+
+    /// The directories the explorer is restricted to. Empty if unrestricted.
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// Returns true if `path` lies inside one of the roots, or there are none.
+    pub fn is_allowed(&self, path: impl AsRef<Path>) -> bool {
+        is_within_roots(&self.roots, path.as_ref())
+    }
+
+    /// Returns true if `path`, with symlinks resolved, lies inside one of the roots, or there are none.
+    fn resolves_within_roots(&self, path: &Path) -> bool {
+        self.roots.is_empty()
+            || self
+                .file_system
+                .absolute(path)
+                .is_ok_and(|resolved| self.is_allowed(resolved))
+    }
+
+    /// Returns true if the roots are shown as the top level, which is the case above a root when there is more than
+    /// one.
+    pub fn is_listing_roots(&self) -> bool {
+        self.roots.len() > 1 && self.current().as_os_str().is_empty()
+    }
+
+    /// Where the explorer opens when it can not open where it was asked to.
+    fn top_level(&self) -> PathBuf {
+        match self.roots.as_slice() {
+            [root] => root.clone(),
+            _ => PathBuf::new(),
+        }
+    }
+
+    fn watch_target(&self) -> Option<PathBuf> {
+        (!self.is_listing_roots()).then(|| self.current().to_path_buf())
+    }
+
+    /// Applies changes to the watched `dir`, unless the explorer has moved on since. The selection is kept, except
+    /// for entries that are gone.
+    fn apply(&mut self, dir: &Path, events: Vec<WatchEvent>) {
+        if dir != self.current() {
+            return;
+        }
+        for event in events {
+            match event {
+                WatchEvent::Created(path) | WatchEvent::Modified(path) => {
+                    self.update_entry(dir, &path);
+                }
+                WatchEvent::Removed(path) => {
+                    self.remove_entry(dir, &path);
+                }
+                WatchEvent::Renamed { from, to } => {
+                    let was_selected = self.remove_entry(dir, &from);
+                    if let Some(entry) = self.update_entry(dir, &to)
+                        && was_selected
+                    {
+                        self.select(&entry);
+                    }
+                }
+                WatchEvent::Rescan => {
+                    self.reload();
+                    return;
+                }
+            }
+        }
+    }
+
+    /// The name of `path` if it is an entry of `dir`.
+    fn name_in(&self, dir: &Path, path: &Path) -> Option<String> {
+        if self.file_system.parent(path)? != dir {
+            return None;
+        }
+        Some(path.file_name()?.to_string_lossy().into_owned())
+    }
+
+    /// Adds or updates the entry of `dir` at `path`, or removes it if it is gone. Returns it if it is listed.
+    fn update_entry(&mut self, dir: &Path, path: &Path) -> Option<ExplorerEntry> {
+        let name = self.name_in(dir, path)?;
+        if self.is_loading {
+            self.changed_while_loading.insert(name.clone());
+        }
+        // Resolved like the listing resolves symlinks
+        let resolved = self.file_system.absolute(path).ok();
+        let metadata = resolved
+            .as_deref()
+            .and_then(|resolved| self.file_system.metadata(resolved).ok());
+        let (Some(resolved), Some(metadata)) = (resolved, metadata) else {
+            self.remove_named(&name);
+            return None;
+        };
+        if !self.is_allowed(&resolved) {
+            self.remove_named(&name);
+            return None;
+        }
+        let entry = self.entry(DirEntry {
+            path: resolved,
+            name,
+            metadata,
+        });
+        match self
+            .entries
+            .iter_mut()
+            .find(|existing| existing.name == entry.name)
+        {
+            Some(existing) => *existing = entry.clone(),
+            None => self.entries.push(entry.clone()),
+        }
+        self.sort_entries();
+        Some(entry)
+    }
+
+    /// Removes the entry of `dir` at `path`. Returns true if it was selected.
+    fn remove_entry(&mut self, dir: &Path, path: &Path) -> bool {
+        let Some(name) = self.name_in(dir, path) else {
+            return false;
+        };
+        if self.is_loading {
+            self.changed_while_loading.insert(name.clone());
+        }
+        self.remove_named(&name)
+    }
+
+    fn remove_named(&mut self, name: &str) -> bool {
+        let Some(index) = self.entries.iter().position(|entry| entry.name == name) else {
+            return false;
+        };
+        let entry = self.entries.remove(index);
+        self.selection.remove(&entry.path)
+    }
+
+    // Sorting
+    //************************************************************************//
+
+    /// How the entries are ordered.
+    pub fn sort(&self) -> SortOptions {
+        self.sort
+    }
+
+    /// Orders the entries by `sort`.
+    pub fn set_sort(&mut self, sort: SortOptions) {
+        self.sort = sort;
+        self.sort_entries();
+    }
+
+    fn sort_entries(&mut self) {
+        let sort = self.sort;
+        self.entries.sort_by(|a, b| compare(sort, a, b));
+    }
+
+    // Selection
+    //************************************************************************//
+
+    /// The selected paths.
+    pub fn selection(&self) -> &HashSet<PathBuf> {
+        &self.selection
+    }
+
+    /// Returns true if `path` is selected.
+    pub fn is_selected(&self, path: impl AsRef<Path>) -> bool {
+        self.selection.contains(path.as_ref())
+    }
+
+    /// If the entry may be added to the selection. Directories can always be entered, regardless.
+    pub fn is_selectable(&self, entry: &ExplorerEntry) -> bool {
+        if !self.is_allowed(&entry.path) {
+            return false;
+        }
+        match entry.kind {
+            FileSystemType::File => self.selection_kind.allows_files() && entry.is_accepted,
+            FileSystemType::Directory => self.selection_kind.allows_directories(),
+        }
+    }
+
+    /// Adds `entry` to the selection, replacing it unless multiple can be selected. Returns false if the entry can
+    /// not be selected.
+    pub fn select(&mut self, entry: &ExplorerEntry) -> bool {
+        if !self.is_selectable(entry) || !self.resolves_within_roots(&entry.path) {
+            return false;
+        }
+        if !self.multiple {
+            self.selection.clear();
+        }
+        self.selection.insert(entry.path.clone());
+        true
+    }
+
+    /// Removes `path` from the selection.
+    pub fn deselect(&mut self, path: impl AsRef<Path>) {
+        self.selection.remove(path.as_ref());
+    }
+
+    /// Selects `entry` if it is not selected, otherwise deselects it.
+    pub fn toggle(&mut self, entry: &ExplorerEntry) {
+        if self.is_selected(&entry.path) {
+            self.deselect(&entry.path);
+        } else {
+            self.select(entry);
+        }
+    }
+
+    /// Empties the selection.
+    pub fn clear_selection(&mut self) {
+        self.selection.clear();
+    }
+
+    /// Takes the selection, leaving it empty. Only paths inside the roots are returned.
+    pub fn submit(&mut self) -> VirtualPaths {
+        self.is_selecting = false;
+        let mut selection = mem::take(&mut self.selection);
+        // Checked again, a symlink may have been pointed out of the roots since it was selected
+        selection.retain(|path| self.resolves_within_roots(path));
+        if self.file_system.is_local() {
+            VirtualPaths::native(selection)
+        } else {
+            VirtualPaths::native_in(selection, self.file_system.clone())
+        }
+    }
+
+    pub(crate) fn toggle_selecting(&mut self) {
+        self.selection.clear();
+        self.is_selecting = !self.is_selecting;
+    }
+
+    /// Which entries can be selected.
+    pub fn selection_kind(&self) -> SelectionKind {
+        self.selection_kind
+    }
+
+    /// What is browsed.
+    pub fn file_system(&self) -> &FileSystem {
+        &self.file_system
+    }
+
+    // Nav
+    //************************************************************************//
+
+    /// Returns true if the current directory has no parent.
+    pub fn is_root(&self) -> bool {
+        self.is_root
+    }
+
+    /// Opens `path`. With roots, paths outside them are refused with an [FileExplorer::error].
+    pub fn navigate(&mut self, path: impl Into<PathBuf>) {
+        let mut path = path.into();
+        if !self.roots.is_empty() {
+            // Resolves symlinks, which could point out of the roots
+            path = self.file_system.absolute(&path).unwrap_or(path);
+            if !self.is_allowed(&path) {
+                self.error = Some(format!(
+                    "{} is outside the allowed directories",
+                    path.display()
+                ));
+                return;
+            }
+        }
+        self.history_add(path);
+        self.reload();
+    }
+
+    /// Opens the directory containing `path` and returns its entry there, without waiting for the listing. None if
+    /// it does not exist or is outside the roots.
+    pub fn reveal(&mut self, path: impl AsRef<Path>) -> Option<ExplorerEntry> {
+        let path = path.as_ref();
+        let entry = self.entry_at(path)?;
+        let parent = self.file_system.parent(path)?;
+        if parent != self.current() {
+            self.navigate(parent);
+        }
+        Some(entry)
+    }
+
+    /// The entry at `path`, which does not have to be in the current directory, e.g. to select it from elsewhere.
+    /// None if it does not exist or is outside the roots.
+    pub fn entry_at(&self, path: impl AsRef<Path>) -> Option<ExplorerEntry> {
+        let path = path.as_ref();
+        // Resolves symlinks, which could point out of the roots
+        let resolved = self.file_system.absolute(path).ok()?;
+        if !self.is_allowed(&resolved) {
+            return None;
+        }
+        let metadata = self.file_system.metadata(&resolved).ok()?;
+        let name = path.file_name()?.to_string_lossy().into_owned();
+        Some(self.entry(DirEntry {
+            path: resolved,
+            name,
+            metadata,
+        }))
+    }
+
+    /// Opens the parent of the current directory. Does nothing at the root.
+    pub fn go_up(&mut self) {
+        if self.is_listing_roots() {
+            return;
+        }
+        let current = self.current();
+        let parent = if self.roots.iter().any(|root| root == current) {
+            (self.roots.len() > 1).then(PathBuf::new)
+        } else {
+            self.file_system.parent(current)
+        };
+        let Some(parent) = parent else {
+            return;
+        };
+        self.history_add(parent);
+        self.reload();
+    }
+
+    /// Opens the previous directory in the history. Does nothing if there is none.
+    pub fn go_back(&mut self) {
+        if !self.can_go_back() {
+            return;
+        }
+        self.history_position += 1;
+        self.filter.clear();
+        self.reload();
+    }
+
+    /// Opens the next directory in the history, after going back. Does nothing if there is none.
+    pub fn go_forward(&mut self) {
+        if !self.can_go_forward() {
+            return;
+        }
+        self.history_position -= 1;
+        self.filter.clear();
+        self.reload();
+    }
+
+    /// Returns true if there is a previous directory in the history.
+    pub fn can_go_back(&self) -> bool {
+        self.history_position + 1 < self.history.len()
+    }
+
+    /// Returns true if there is a next directory in the history.
+    pub fn can_go_forward(&self) -> bool {
+        self.history_position > 0
+    }
+
+    fn history_add(&mut self, path: PathBuf) {
+        if self.history_position != 0 {
+            self.history
+                .truncate(self.history.len() - self.history_position);
+            self.history_position = 0;
+        }
+        self.history.push(path);
+        self.filter.clear();
+    }
+}
+
+/// If `path` is inside one of `roots`, or `roots` is empty. Symlinks are not resolved, that is up to the caller.
+pub(crate) fn is_within_roots(roots: &[PathBuf], path: &Path) -> bool {
+    if roots.is_empty() {
+        return true;
+    }
+    // `..` could climb out while still starting with a root
+    !path
+        .components()
+        .any(|component| component == Component::ParentDir)
+        && roots.iter().any(|root| path.starts_with(root))
+}
+
+fn compare(sort: SortOptions, a: &ExplorerEntry, b: &ExplorerEntry) -> Ordering {
+    if sort.folders_first && a.is_dir() != b.is_dir() {
+        return if a.is_dir() {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        };
+    }
+    let by_name = || natural_cmp(&a.name, &b.name);
+    let ordering = match sort.by {
+        SortBy::Name => by_name(),
+        SortBy::Size => a
+            .size
+            .unwrap_or(0)
+            .cmp(&b.size.unwrap_or(0))
+            .then_with(by_name),
+        SortBy::Modified => a.modified.cmp(&b.modified).then_with(by_name),
+        SortBy::Type => natural_cmp(&a.extension(), &b.extension()).then_with(by_name),
+    };
+    sort.order.apply(ordering)
+}
+
+/// A file or directory listed by a [FileExplorer].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExplorerEntry {
+    kind: FileSystemType,
+    path: PathBuf,
+    name: String,
+    is_accepted: bool,
+    size: Option<u64>,
+    modified: Option<SystemTime>,
+    permissions: Option<u32>,
+    owner: Option<u32>,
+}
+
+impl ExplorerEntry {
+    /// If the entry is a file or a directory. Symlinks are resolved.
+    pub fn kind(&self) -> FileSystemType {
+        self.kind
+    }
+
+    /// Returns true if the entry is a directory.
+    pub fn is_dir(&self) -> bool {
+        self.kind == FileSystemType::Directory
+    }
+
+    /// The resolved path.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Usually the file name of [ExplorerEntry::path], except when the entry is a symlink.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// If the entry matches the explorer's [Accept] spec. Always true for directories.
+    pub fn is_accepted(&self) -> bool {
+        self.is_accepted
+    }
+
+    /// The size in bytes. None for directories.
+    pub fn size(&self) -> Option<u64> {
+        self.size
+    }
+
+    /// The MIME type guessed from the file's extension. None for directories and unknown extensions.
+    pub fn mime_type(&self) -> Option<&'static str> {
+        if self.is_dir() {
+            return None;
+        }
+        mime_guess::from_path(&self.name).first_raw()
+    }
+
+    /// The Unix permission bits, e.g. `0o644`, where the file system has them.
+    pub fn permissions(&self) -> Option<u32> {
+        self.permissions
+    }
+
+    /// The user id of the owner, where the file system has one.
+    pub fn owner(&self) -> Option<u32> {
+        self.owner
+    }
+
+    /// The lowercase extension of a file, empty for directories and files without one.
+    fn extension(&self) -> String {
+        if self.is_dir() {
+            return String::new();
+        }
+        Path::new(&self.name)
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default()
+    }
+
+    /// When the entry was last modified, if the platform reports it.
+    pub fn modified(&self) -> Option<SystemTime> {
+        self.modified
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::EntryMetadata;
+
+    /// An empty directory for a test to create files in, removed again when dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir =
+                env::temp_dir().join(format!("dioxus_file_picker_{}_{name}", std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            // Resolved, like the explorer resolves its roots
+            Self(fs::canonicalize(dir).unwrap())
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn explorer_in(roots: &[&Path], open_at: &Path) -> FileExplorer {
+        FileExplorer::new(ExplorerOptions {
+            open_at: Some(open_at.to_path_buf()),
+            roots: roots.iter().map(|root| root.to_path_buf()).collect(),
+            ..Default::default()
+        })
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_out_of_the_roots_are_rejected() {
+        let temp = TempDir::new("symlink_jail");
+        let root = temp.0.join("root");
+        let outside = temp.0.join("outside");
+        fs::create_dir_all(&root).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(root.join("inside.txt"), "inside").unwrap();
+        fs::write(outside.join("secret.txt"), "secret").unwrap();
+        let link = root.join("link.txt");
+        std::os::unix::fs::symlink(outside.join("secret.txt"), &link).unwrap();
+
+        let mut explorer = explorer_in(&[&root], &root);
+        assert!(explorer.entry_at(root.join("inside.txt")).is_some());
+        assert!(explorer.entry_at(&link).is_none());
+
+        // As listed from the link, before it is resolved
+        let link_entry = explorer.entry(DirEntry {
+            path: link.clone(),
+            name: "link.txt".to_owned(),
+            metadata: explorer.file_system.metadata(&link).unwrap(),
+        });
+        assert!(!explorer.select(&link_entry));
+
+        // Selected while it still pointed inside
+        explorer.selection.insert(link.clone());
+        explorer.selection.insert(root.join("inside.txt"));
+        assert_eq!(
+            explorer.submit().paths(),
+            HashSet::from([root.join("inside.txt").to_string_lossy().into_owned()])
+        );
+    }
+
+    fn explorer_at(path: &str) -> FileExplorer {
+        explorer_in(&[], Path::new(path))
+    }
+
+    #[test]
+    fn navigating_after_going_back_drops_the_forward_history() {
+        let mut explorer = explorer_at("/a");
+        explorer.navigate("/a/b");
+        explorer.navigate("/a/b/c");
+        explorer.go_back();
+        explorer.go_back();
+        assert_eq!(explorer.current(), Path::new("/a"));
+        assert!(explorer.can_go_forward());
+        explorer.navigate("/d");
+        assert_eq!(explorer.current(), Path::new("/d"));
+        assert!(!explorer.can_go_forward());
+        explorer.go_back();
+        assert_eq!(explorer.current(), Path::new("/a"));
+        assert!(!explorer.can_go_back());
+    }
+
+    #[test]
+    fn going_back_and_forward_stops_at_the_ends() {
+        let mut explorer = explorer_at("/a");
+        explorer.navigate("/b");
+        explorer.go_forward();
+        assert_eq!(explorer.current(), Path::new("/b"));
+        explorer.go_back();
+        explorer.go_back();
+        assert_eq!(explorer.current(), Path::new("/a"));
+        explorer.go_forward();
+        assert_eq!(explorer.current(), Path::new("/b"));
+    }
+
+    #[test]
+    fn restoring_skips_directories_that_are_gone() {
+        let temp = TempDir::new("restore");
+        let (a, b) = (temp.0.join("a"), temp.0.join("b"));
+        fs::create_dir_all(&a).unwrap();
+        fs::create_dir_all(&b).unwrap();
+        let session = |history_position| PickerSession {
+            history: vec![a.clone(), temp.0.join("gone"), b.clone()],
+            history_position,
+            view: ViewSettings {
+                is_selecting: true,
+                layout: Layout::List,
+                ..ViewSettings::default()
+            },
+        };
+
+        let explorer = FileExplorer::restore(session(0), ExplorerOptions::default()).unwrap();
+        assert_eq!(explorer.current(), b);
+        assert!(explorer.can_go_back());
+        assert!(explorer.is_selecting);
+        assert_eq!(explorer.layout, Layout::List);
+
+        // The current directory is gone, so it opens at the one before
+        let explorer = FileExplorer::restore(session(1), ExplorerOptions::default()).unwrap();
+        assert_eq!(explorer.current(), a);
+        assert!(explorer.can_go_forward());
+
+        let gone = PickerSession {
+            history: vec![temp.0.join("gone")],
+            ..PickerSession::default()
+        };
+        assert!(FileExplorer::restore(gone, ExplorerOptions::default()).is_none());
+    }
+
+    #[test]
+    fn restoring_skips_directories_outside_the_roots() {
+        let temp = TempDir::new("restore_roots");
+        let (root, outside) = (temp.0.join("root"), temp.0.join("outside"));
+        fs::create_dir_all(&root).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        let session = PickerSession {
+            history: vec![root.clone(), outside],
+            ..PickerSession::default()
+        };
+        let options = ExplorerOptions {
+            roots: vec![root.clone()],
+            ..ExplorerOptions::default()
+        };
+        let explorer = FileExplorer::restore(session, options).unwrap();
+        assert_eq!(explorer.current(), root);
+        assert!(!explorer.can_go_back());
+    }
+
+    #[test]
+    fn several_roots_are_listed_as_the_top_level() {
+        let temp = TempDir::new("roots_top_level");
+        let (first, second) = (temp.0.join("first"), temp.0.join("second"));
+        fs::create_dir_all(&first).unwrap();
+        fs::create_dir_all(&second).unwrap();
+
+        // Opening outside the roots opens at the top level
+        let mut explorer = explorer_in(&[&first, &second], &temp.0);
+        assert!(explorer.is_listing_roots());
+        assert!(explorer.is_root());
+        let listed: Vec<&Path> = explorer.entries().iter().map(ExplorerEntry::path).collect();
+        assert_eq!(listed, [first.as_path(), second.as_path()]);
+
+        explorer.navigate(&first);
+        assert!(!explorer.is_listing_roots());
+        assert!(!explorer.is_root());
+        explorer.go_up();
+        assert!(explorer.is_listing_roots());
+        // Nothing above the top level
+        explorer.go_up();
+        assert!(explorer.is_listing_roots());
+        assert!(!explorer.can_go_forward());
+
+        explorer.navigate(&temp.0);
+        assert!(explorer.is_listing_roots());
+        assert!(explorer.error().is_some());
+    }
+
+    #[test]
+    fn a_single_root_is_the_top_level() {
+        let temp = TempDir::new("single_root");
+        let root = temp.0.join("root");
+        fs::create_dir_all(&root).unwrap();
+        let mut explorer = explorer_in(&[&root], &temp.0);
+        assert_eq!(explorer.current(), root);
+        assert!(explorer.is_root());
+        explorer.go_up();
+        assert_eq!(explorer.current(), root);
+    }
+
+    fn file_entry(dir: &Path, name: &str) -> DirEntry {
+        DirEntry {
+            path: dir.join(name),
+            name: name.to_owned(),
+            metadata: EntryMetadata {
+                kind: FileSystemType::File,
+                size: Some(0),
+                modified: None,
+                permissions: None,
+                owner: None,
+            },
+        }
+    }
+
+    fn names(explorer: &FileExplorer) -> Vec<&str> {
+        explorer.entries().iter().map(ExplorerEntry::name).collect()
+    }
+
+    #[test]
+    fn batches_of_a_replaced_listing_are_dropped() {
+        let mut explorer = explorer_at("/a");
+        let stale = explorer.generation;
+        explorer.navigate("/b");
+        let current = explorer.generation;
+        assert_ne!(stale, current);
+
+        explorer.receive(
+            stale,
+            ListingEvent::Entries(vec![file_entry(Path::new("/a"), "old")]),
+        );
+        explorer.receive(stale, ListingEvent::Done);
+        assert!(explorer.entries().is_empty());
+        assert!(explorer.is_loading());
+
+        explorer.receive(
+            current,
+            ListingEvent::Entries(vec![file_entry(Path::new("/b"), "new")]),
+        );
+        assert_eq!(names(&explorer), ["new"]);
+        assert!(explorer.is_loading());
+        explorer.receive(current, ListingEvent::Done);
+        assert!(!explorer.is_loading());
+
+        // Nothing is added once the listing is done
+        explorer.receive(
+            current,
+            ListingEvent::Entries(vec![file_entry(Path::new("/b"), "late")]),
+        );
+        assert_eq!(names(&explorer), ["new"]);
+    }
+
+    #[test]
+    fn a_failed_listing_stops_loading_with_an_error() {
+        let mut explorer = explorer_at("/a");
+        let generation = explorer.generation;
+        explorer.receive(
+            generation,
+            ListingEvent::Failed(io::ErrorKind::PermissionDenied.into()),
+        );
+        assert!(!explorer.is_loading());
+        assert!(explorer.error().is_some());
+    }
+
+    /// An explorer at `dir` that finished listing it, but has not seen its files.
+    fn listed(dir: &Path) -> FileExplorer {
+        let mut explorer = explorer_in(&[], dir);
+        explorer.receive(explorer.generation, ListingEvent::Done);
+        explorer
+    }
+
+    #[test]
+    fn watch_events_update_the_entries() {
+        let temp = TempDir::new("watch_events");
+        let mut explorer = listed(&temp.0);
+        let (a, b) = (temp.0.join("a.txt"), temp.0.join("b.txt"));
+
+        fs::write(&a, "a").unwrap();
+        explorer.apply(&temp.0, vec![WatchEvent::Created(a.clone())]);
+        assert_eq!(names(&explorer), ["a.txt"]);
+
+        fs::write(&a, "changed").unwrap();
+        explorer.apply(&temp.0, vec![WatchEvent::Modified(a.clone())]);
+        assert_eq!(explorer.entries()[0].size(), Some(7));
+
+        fs::remove_file(&a).unwrap();
+        explorer.apply(&temp.0, vec![WatchEvent::Removed(a.clone())]);
+        assert!(explorer.entries().is_empty());
+
+        // An event for a file that is gone again by the time it is applied
+        explorer.apply(&temp.0, vec![WatchEvent::Created(b)]);
+        assert!(explorer.entries().is_empty());
+    }
+
+    #[test]
+    fn a_renamed_entry_stays_selected() {
+        let temp = TempDir::new("watch_rename");
+        let mut explorer = listed(&temp.0);
+        let (from, to) = (temp.0.join("from.txt"), temp.0.join("to.txt"));
+        fs::write(&from, "").unwrap();
+        explorer.apply(&temp.0, vec![WatchEvent::Created(from.clone())]);
+        let entry = explorer.entries()[0].clone();
+        assert!(explorer.select(&entry));
+
+        fs::rename(&from, &to).unwrap();
+        explorer.apply(
+            &temp.0,
+            vec![WatchEvent::Renamed {
+                from: from.clone(),
+                to: to.clone(),
+            }],
+        );
+        assert_eq!(names(&explorer), ["to.txt"]);
+        assert_eq!(explorer.selection(), &HashSet::from([to.clone()]));
+
+        fs::remove_file(&to).unwrap();
+        explorer.apply(&temp.0, vec![WatchEvent::Removed(to)]);
+        assert!(explorer.selection().is_empty());
+    }
+
+    #[test]
+    fn watch_events_of_another_directory_are_ignored() {
+        let temp = TempDir::new("watch_other");
+        let mut explorer = listed(&temp.0);
+        let other = temp.0.join("other");
+        fs::create_dir_all(&other).unwrap();
+        fs::write(other.join("a.txt"), "").unwrap();
+        explorer.apply(&other, vec![WatchEvent::Created(other.join("a.txt"))]);
+        // Not an entry of the watched directory
+        explorer.apply(&temp.0, vec![WatchEvent::Created(other.join("a.txt"))]);
+        assert!(explorer.entries().is_empty());
+    }
+
+    #[test]
+    fn entries_changed_while_listing_are_not_listed_again() {
+        let temp = TempDir::new("watch_while_listing");
+        let mut explorer = explorer_in(&[], &temp.0);
+        let generation = explorer.generation;
+        fs::write(temp.0.join("new.txt"), "fresh").unwrap();
+        explorer.apply(&temp.0, vec![WatchEvent::Created(temp.0.join("new.txt"))]);
+        // The listing read the file before it changed
+        let mut stale = file_entry(&temp.0, "new.txt");
+        stale.metadata.size = Some(1);
+        explorer.receive(generation, ListingEvent::Entries(vec![stale]));
+        explorer.receive(generation, ListingEvent::Done);
+        assert_eq!(names(&explorer), ["new.txt"]);
+        assert_eq!(explorer.entries()[0].size(), Some(5));
+    }
+
+    #[test]
+    fn a_rescan_lists_again() {
+        let temp = TempDir::new("watch_rescan");
+        let mut explorer = listed(&temp.0);
+        let generation = explorer.generation;
+        explorer.apply(&temp.0, vec![WatchEvent::Rescan]);
+        assert!(explorer.is_loading());
+        assert_ne!(explorer.generation, generation);
+    }
+}
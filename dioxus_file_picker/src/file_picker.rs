@@ -1,73 +1,151 @@
-use std::{
-    collections::HashSet,
-    env, fs, mem,
-    path::{Path, PathBuf},
-};
+use std::path::PathBuf;
 
-use dioxus::{logger::tracing::error, prelude::*};
+use dioxus::prelude::*;
 
-use crate::VirtualPaths;
+use crate::{
+    Accept, ExplorerOptions, FileExplorer, FileSystem, FileSystemType, SelectionKind, SortBy,
+    SortOptions, SortOrder, VirtualPaths,
+    content_search::ContentSearch,
+    details::DetailsView,
+    explorer::use_listing,
+    filter::{FilterBox, Highlighted},
+    path_bar::PathBar,
+    places::{self, PlacesSidebar},
+    preview::PreviewPane,
+    quick_open::QuickOpen,
+    session::{self, Layout},
+    thumbnails::FileIcon,
+};
 
 /// A file picker component that works on desktop and mobile.
 #[component]
 pub(crate) fn FilePicker(
     multiple: bool,
+    #[props(default)] accept: Accept,
+    #[props(default)] selection: SelectionKind,
     open_at: Option<PathBuf>,
     on_submit: Callback<VirtualPaths, ()>,
+    /// If Some, a cancel button is shown which calls it
+    on_cancel: Option<Callback<(), ()>>,
+    /// If Some, where the picker is left is stored under this key and restored when it opens without `open_at`. The
+    /// sort is restored either way
+    persistence_key: Option<String>,
+    /// What is browsed. Defaults to the local disk.
+    #[props(default)]
+    file_system: FileSystem,
+    /// If not empty, the only directories that can be browsed
+    #[props(default)]
+    roots: Vec<PathBuf>,
+    /// The sort until the user changes it
+    #[props(default)]
+    sort: SortOptions,
 ) -> Element {
-    let mut explorer = use_signal(|| match open_at {
-        Some(path) => FilesExplorerState::init_at(path),
-        None => FilesExplorerState::new(),
+    let restore_key = persistence_key.clone();
+    let mut explorer = use_signal(|| {
+        let session = restore_key.and_then(|key| session::load(&key));
+        let restore = open_at.is_none();
+        let options = ExplorerOptions {
+            open_at,
+            accept,
+            selection,
+            multiple,
+            file_system,
+            roots,
+            sort: session.as_ref().map_or(sort, |session| session.view.sort),
+        };
+        let restored = match session {
+            Some(session) if restore => FileExplorer::restore(session, options.clone()),
+            _ => None,
+        };
+        restored.unwrap_or_else(|| FileExplorer::new(options))
     });
-    let reader = explorer.read();
-    rsx! {
-        document::Link { rel: "stylesheet", href: asset!("/assets/tailwind.css") }
-        document::Link {
-            rel: "stylesheet",
-            href: asset!("/assets/integrated_file_picker.css"),
+    use_listing(explorer);
+    // Only changes on navigation, not on every selection
+    let current_session = use_memo(move || explorer.read().session());
+    use_effect(move || {
+        let session = current_session.read().clone();
+        if let Some(key) = persistence_key.clone() {
+            tokio::task::spawn_blocking(move || session::store(&key, session));
         }
-        document::Link {
-            href: "https://fonts.googleapis.com/icon?family=Material+Icons",
-            rel: "stylesheet",
+    });
+    let mut show_places = use_signal(|| true);
+    // Loaded again whenever the sidebar is shown, volumes may have been mounted since
+    let places = use_resource(move || async move {
+        if !show_places() {
+            return None;
         }
+        tokio::task::spawn_blocking(places::load).await.ok()
+    });
+    let mut show_preview = use_signal(|| false);
+    let mut show_quick_open = use_signal(|| false);
+    let mut show_content_search = use_signal(|| false);
+    let reader = explorer.read();
+    rsx! {
+        Stylesheets {}
         // File Explorer
-        div { class: "flex flex-col h-full bg-white",
+        div {
+            class: "flex flex-col h-full bg-white",
+            onkeydown: move |event| {
+                let modifiers = event.modifiers();
+                let is_quick_open = (modifiers.ctrl() || modifiers.meta())
+                    && event.key() == Key::Character("p".to_owned());
+                if is_quick_open && !explorer.read().is_listing_roots() {
+                    event.prevent_default();
+                    show_content_search.set(false);
+                    show_quick_open.set(true);
+                }
+            },
             // File Explorer header
             div { class: "flex items-center flex-row ",
-                if reader.can_go_back() {
-                    i {
-                        class: "material-icons",
-                        onclick: move |_| explorer.write().go_back(),
-                        "arrow_back"
-                    }
-                } else {
-                    i { class: "material-icons deactivated", "arrow_back" }
+                i {
+                    class: "material-icons",
+                    title: if show_places() { "Hide places" } else { "Show places" },
+                    onclick: move |_| show_places.toggle(),
+                    "menu"
                 }
-                if reader.can_go_forward() {
+                NavigationControls { explorer }
+                if !reader.is_listing_roots() {
                     i {
                         class: "material-icons",
-                        onclick: move |_| explorer.write().go_forward(),
-                        "arrow_forward"
+                        title: "Quick open (Ctrl+P)",
+                        onclick: move |_| {
+                            show_content_search.set(false);
+                            show_quick_open.toggle();
+                        },
+                        "search"
                     }
-                } else {
-                    i { class: "material-icons deactivated", "arrow_forward" }
-                }
-                if reader.is_root {
-                    i { class: "material-icons deactivated", "arrow_upward" }
-                } else {
                     i {
                         class: "material-icons",
-                        onclick: move |_| explorer.write().go_up(),
-                        "arrow_upward"
+                        title: "Search file contents",
+                        onclick: move |_| {
+                            show_quick_open.set(false);
+                            show_content_search.toggle();
+                        },
+                        "find_in_page"
                     }
                 }
-                div { class: "border border-white-500 p-4 rounded-md flex-grow overflow-x-auto",
-                    {reader.current().display().to_string()}
+                FilterBox { explorer }
+                SortControls { explorer }
+                i {
+                    class: "material-icons",
+                    title: "Switch between grid and list",
+                    onclick: move |_| {
+                        let mut writer = explorer.write();
+                        writer.layout = match writer.layout {
+                            Layout::Grid => Layout::List,
+                            Layout::List => Layout::Grid,
+                        };
+                    },
+                    match reader.layout {
+                        Layout::Grid => "view_list",
+                        Layout::List => "grid_view",
+                    }
                 }
                 i {
                     class: "material-icons",
-                    onclick: move |_| explorer.write().reload(),
-                    "refresh"
+                    title: if show_preview() { "Hide preview" } else { "Show preview" },
+                    onclick: move |_| show_preview.toggle(),
+                    "preview"
                 }
                 div {
                     class: "border border-white-500 p-4 rounded-md",
@@ -80,89 +158,128 @@ pub(crate) fn FilePicker(
                     }
                 }
             }
-            // File Explorer Content
-            div { class: "flex-1 overflow-y-auto",
-                if let Some(err) = reader.error.as_ref() {
-                    div {
-                        code { "{err}" }
-                        button { onclick: move |_| explorer.write().error = None, "x" }
-                    }
-                } else {
-                    div {
-                        for entity in reader.current_entities.clone() {
-                            {
-                                let name = entity.name;
-                                let selection_class = if reader.selection.contains(&entity.path) {
-                                    "bg-blue-500"
-                                } else {
-                                    ""
-                                };
-                                match entity.r#type {
-                                    FileSystemType::File => rsx! {
-                                        div { class: "fse {selection_class}",
-                                            i {
-                                                class: "material-icons",
-                                                onclick: move |_| {
-                                                    let mut writer = explorer.write();
-                                                    let path = entity.path.clone();
-                                                    if writer.is_selecting {
-                                                        if writer.selection.contains(&path) {
-                                                            writer.selection.retain(|p| p != &path);
-                                                        } else {
-                                                            if !multiple {
-                                                                writer.selection.clear();
+            div { class: "fp-picker-body",
+                if show_quick_open() {
+                    QuickOpen { explorer, on_close: move |_| show_quick_open.set(false) }
+                }
+                if show_content_search() {
+                    ContentSearch { explorer, on_close: move |_| show_content_search.set(false) }
+                }
+                if let Some(Some(places)) = places() {
+                    PlacesSidebar { explorer, places }
+                }
+                // File Explorer Content
+                div { class: "flex-1 overflow-y-auto",
+                    if let Some(err) = reader.error() {
+                        div {
+                            code { "{err}" }
+                            button { onclick: move |_| explorer.write().dismiss_error(), "x" }
+                        }
+                    } else {
+                        LoadingIndicator { explorer }
+                        if reader.layout == Layout::List {
+                            DetailsView { explorer }
+                        } else {
+                            div {
+                                for (entity , highlights) in reader.visible_entries() {
+                                    {
+                                        let is_selectable = reader.is_selectable(&entity);
+                                        let is_local = reader.file_system().is_local();
+                                        let name = entity.name().to_owned();
+                                        // Keyed, so entries added or removed while watching keep the rest in place
+                                        let key = entity.path().display().to_string();
+                                        let selection_class = if reader.is_selected(entity.path()) {
+                                            "bg-blue-500"
+                                        } else {
+                                            ""
+                                        };
+                                        match entity.kind() {
+                                            FileSystemType::File if !is_selectable => rsx! {
+                                                div {
+                                                    key: "{key}",
+                                                    class: "fse",
+                                                    FileIcon {
+                                                        path: entity.path().to_path_buf(),
+                                                        modified: entity.modified(),
+                                                        is_local,
+                                                        deactivated: true,
+                                                        onclick: move |_| explorer.write().focused = Some(entity.path().to_path_buf()),
+                                                    }
+                                                    h1 { class: "deactivated",
+                                                        Highlighted { text: name, ranges: highlights }
+                                                    }
+                                                }
+                                            },
+                                            FileSystemType::File => rsx! {
+                                                div {
+                                                    key: "{key}",
+                                                    class: "fse {selection_class}",
+                                                    FileIcon {
+                                                        path: entity.path().to_path_buf(),
+                                                        modified: entity.modified(),
+                                                        is_local,
+                                                        onclick: move |_| {
+                                                            let mut writer = explorer.write();
+                                                            writer.focused = Some(entity.path().to_path_buf());
+                                                            if writer.is_selecting {
+                                                                writer.toggle(&entity);
                                                             }
-                                                            writer.selection.insert(path);
-                                                        }
+                                                        },
                                                     }
-                                                },
-                                                "description"
-                                            }
-                                            h1 { "{name}" }
-                                        }
-                                    },
-                                    FileSystemType::Directory => rsx! {
-                                        div { class: "fse {selection_class}",
-                                            i {
-                                                class: "material-icons ",
-                                                onclick: move |_| {
-                                                    let mut writer = explorer.write();
-                                                    let path = entity.path.clone();
-                                                    if writer.is_selecting {
-                                                        if writer.selection.contains(&path) {
-                                                            writer.selection.retain(|p| p != &path);
-                                                        } else {
-                                                            if !multiple {
-                                                                writer.selection.clear();
+                                                    h1 {
+                                                        Highlighted { text: name, ranges: highlights }
+                                                    }
+                                                }
+                                            },
+                                            FileSystemType::Directory => rsx! {
+                                                div {
+                                                    key: "{key}",
+                                                    class: "fse {selection_class}",
+                                                    i {
+                                                        class: "material-icons ",
+                                                        onclick: move |_| {
+                                                            let mut writer = explorer.write();
+                                                            if writer.is_selecting && writer.selection_kind().allows_directories() {
+                                                                writer.focused = Some(entity.path().to_path_buf());
+                                                                writer.toggle(&entity);
+                                                            } else {
+                                                                writer.navigate(entity.path())
                                                             }
-                                                            writer.selection.insert(path);
-                                                        }
-                                                    } else {
-                                                        writer.enter_dir(path)
+                                                        },
+                                                        "folder"
                                                     }
-                                                },
-                                                "folder"
-                                            }
-                                            h1 { "{name}" }
+                                                    h1 {
+                                                        Highlighted { text: name, ranges: highlights }
+                                                    }
+                                                }
+                                            },
                                         }
-                                    },
+                                    }
                                 }
+
                             }
                         }
-                    
                     }
                 }
+                if show_preview() {
+                    PreviewPane { explorer }
+                }
             }
-            // Footer (Always in layout, button only shows when selecting)
-            div { class: "bg-amber-50 p-4 border-t border-gray-300",
+            // Footer (Always in layout, submit button only shows when selecting)
+            div { class: "bg-amber-50 p-4 border-t border-gray-300 fp-row",
+                if let Some(on_cancel) = on_cancel {
+                    button {
+                        class: "border border-gray-300 px-4 py-2 rounded",
+                        onclick: move |_| on_cancel.call(()),
+                        "Cancel"
+                    }
+                }
                 if reader.is_selecting {
                     button {
                         class: "bg-blue-500 text-white px-4 py-2 rounded hover:bg-blue-600",
                         onclick: move |_| {
-                            let mut writer = explorer.write();
-                            let selection = mem::take(&mut writer.selection);
-                            writer.is_selecting = false;
-                            on_submit.call(VirtualPaths::native(selection));
+                            let paths = explorer.write().submit();
+                            on_submit.call(paths);
                         },
                         "Submit"
                     }
@@ -172,193 +289,126 @@ pub(crate) fn FilePicker(
     }
 }
 
-/// A simple little struct to hold the file explorer state
-///
-/// We don't use any fancy signals or memoization here - Dioxus is so fast that even a file explorer can be done with a
-/// single signal.
-struct FilesExplorerState {
-    /// Always canonicalized
-    current_entities: Vec<TypedPathBuf>,
-    is_root: bool,
-    history: Vec<PathBuf>,
-    history_position: usize,
-    selection: HashSet<PathBuf>,
-    is_selecting: bool,
-    /// If Some, an error occurred with the current operation
-    error: Option<String>,
+/// The stylesheets used by the integrated file picker and file saver.
+#[component]
+pub(crate) fn Stylesheets() -> Element {
+    rsx! {
+        document::Link { rel: "stylesheet", href: asset!("/assets/tailwind.css") }
+        document::Link {
+            rel: "stylesheet",
+            href: asset!("/assets/integrated_file_picker.css"),
+        }
+        document::Link {
+            href: "https://fonts.googleapis.com/icon?family=Material+Icons",
+            rel: "stylesheet",
+        }
+    }
 }
 
-impl FilesExplorerState {
-    fn new() -> Self {
-        Self::init_at(
-            env::current_dir()
-                .expect("Failed to retrieve current working directory using env::current_dir()"),
-        )
+/// Shown while the current directory is being listed.
+#[component]
+pub(crate) fn LoadingIndicator(explorer: Signal<FileExplorer>) -> Element {
+    if !explorer.read().is_loading() {
+        return rsx! {};
     }
-
-    fn init_at(path: impl AsRef<Path>) -> Self {
-        let path = path.as_ref();
-        let current = std::path::absolute(path).expect(&format!(
-            "Could not get absolute path of {}",
-            path.display()
-        ));
-        let mut explorer = Self {
-            current_entities: vec![],
-            is_root: false,
-            history: vec![current],
-            history_position: 0,
-            selection: HashSet::new(),
-            is_selecting: false,
-            error: None,
-        };
-
-        explorer.reload();
-
-        explorer
+    rsx! {
+        div { class: "fp-loading fp-muted",
+            i { class: "material-icons fp-spin", "autorenew" }
+            "Loading…"
+        }
     }
+}
 
-    fn reload(&mut self) {
-        let current_path = self.current();
-        let current_file_system_entities = match std::fs::read_dir(current_path) {
-            Ok(e) => e,
-            Err(err) => {
-                let error = format!("An error occurred: {err:?}");
-                self.error = Some(error);
-                return;
-            }
-        };
-
-        self.is_root = current_path.parent().is_none();
-        self.error = None;
-
-        self.current_entities.clear();
-        for entity in current_file_system_entities {
-            // todo make async and use async
-            let typed_path: Option<TypedPathBuf> = (|| {
-                let entity = entity.ok()?;
-                let mut path = entity.path();
-                let name = path.file_name()?.to_string_lossy().to_string();
-                let entity_type = entity.file_type().ok()?;
-                let fs_type;
-                if entity_type.is_dir() {
-                    fs_type = FileSystemType::Directory;
-                } else if entity_type.is_symlink() {
-                    path = fs::canonicalize(path).ok()?;
-                    let metadata = fs::metadata(&path).ok()?;
-                    debug_assert!(
-                        !metadata.is_symlink(),
-                        "canonicalize should resolve symlinks"
-                    );
-                    if metadata.is_dir() {
-                        fs_type = FileSystemType::Directory;
-                    } else {
-                        fs_type = FileSystemType::File;
+/// What the entries are sorted by, the order and if folders come first.
+#[component]
+fn SortControls(explorer: Signal<FileExplorer>) -> Element {
+    let sort = explorer.read().sort();
+    rsx! {
+        div { class: "fp-row fp-sort",
+            select {
+                title: "Sort by",
+                onchange: move |event| {
+                    let value = event.value();
+                    if let Some(by) = SortBy::ALL.into_iter().find(|by| by.label() == value) {
+                        explorer.write().set_sort(SortOptions { by, ..sort });
                     }
-                } else {
-                    fs_type = FileSystemType::File;
-                };
-                // todo check permissions?
-                Some(TypedPathBuf::new(fs_type, path.to_path_buf(), name))
-            })();
-            match typed_path {
-                Some(typed_path) => self.current_entities.push(typed_path),
-                None => {
-                    error!("Could not get typed path for entity");
-                    continue;
+                },
+                for by in SortBy::ALL {
+                    option { value: by.label(), selected: by == sort.by, {by.label()} }
                 }
             }
+            i {
+                class: "material-icons",
+                title: "Reverse the order",
+                onclick: move |_| {
+                    explorer
+                        .write()
+                        .set_sort(SortOptions {
+                            order: sort.order.reversed(),
+                            ..sort
+                        });
+                },
+                match sort.order {
+                    SortOrder::Ascending => "arrow_upward",
+                    SortOrder::Descending => "arrow_downward",
+                }
+            }
+            label { class: "fp-row",
+                input {
+                    r#type: "checkbox",
+                    checked: sort.folders_first,
+                    onchange: move |event| {
+                        explorer
+                            .write()
+                            .set_sort(SortOptions {
+                                folders_first: event.checked(),
+                                ..sort
+                            });
+                    },
+                }
+                "Folders first"
+            }
         }
     }
-
-    fn toggle_selecting(&mut self) {
-        self.selection.clear();
-        self.is_selecting = !self.is_selecting;
-    }
-
-    fn current(&self) -> &PathBuf {
-        &self.history[self.history.len() - self.history_position - 1]
-    }
-
-    // Nav
-    //************************************************************************//
-
-    fn enter_dir(&mut self, path: PathBuf) {
-        self.history_add(path);
-        self.reload();
-    }
-
-    fn go_up(&mut self) {
-        debug_assert!(
-            !self.is_root,
-            "This should not be exposed if we are at root"
-        );
-        let new = self
-            .current()
-            .parent()
-            .expect("This should not be exposed if we are at root")
-            .to_path_buf();
-        self.history_add(new);
-        self.reload();
-    }
-
-    fn go_back(&mut self) {
-        debug_assert!(self.can_go_back());
-        self.history_back();
-        self.reload();
-    }
-
-    fn go_forward(&mut self) {
-        debug_assert!(self.can_go_forward());
-        self.history_forward();
-        self.reload();
-    }
-
-    fn can_go_back(&self) -> bool {
-        self.history_position + 1 < self.history.len()
-    }
-
-    fn history_back(&mut self) {
-        self.history_position += 1;
-        self.reload();
-    }
-
-    fn can_go_forward(&self) -> bool {
-        self.history_position > 0
-    }
-
-    fn history_forward(&mut self) {
-        self.history_position -= 1;
-        self.reload();
-    }
-
-    fn history_add(&mut self, path: PathBuf) {
-        if self.history_position != 0 {
-            self.history
-                .truncate(self.history.len() - self.history_position);
-            self.history_position = 0;
-        }
-        self.history.push(path);
-    }
 }
 
-#[derive(Clone)]
-struct TypedPathBuf {
-    /// The file system entity type
-    r#type: FileSystemType,
-    /// The resolved path
-    path: PathBuf,
-    /// Usually `file_name` [path], except when [path] is a symlink
-    name: String,
-}
-
-impl TypedPathBuf {
-    fn new(r#type: FileSystemType, path: PathBuf, name: String) -> Self {
-        Self { r#type, path, name }
+/// Back, forward and up buttons, the path bar and a refresh button.
+#[component]
+pub(crate) fn NavigationControls(explorer: Signal<FileExplorer>) -> Element {
+    let reader = explorer.read();
+    rsx! {
+        if reader.can_go_back() {
+            i {
+                class: "material-icons",
+                onclick: move |_| explorer.write().go_back(),
+                "arrow_back"
+            }
+        } else {
+            i { class: "material-icons deactivated", "arrow_back" }
+        }
+        if reader.can_go_forward() {
+            i {
+                class: "material-icons",
+                onclick: move |_| explorer.write().go_forward(),
+                "arrow_forward"
+            }
+        } else {
+            i { class: "material-icons deactivated", "arrow_forward" }
+        }
+        if reader.is_root() {
+            i { class: "material-icons deactivated", "arrow_upward" }
+        } else {
+            i {
+                class: "material-icons",
+                onclick: move |_| explorer.write().go_up(),
+                "arrow_upward"
+            }
+        }
+        PathBar { explorer }
+        i {
+            class: "material-icons",
+            onclick: move |_| explorer.write().reload(),
+            "refresh"
+        }
     }
 }
-
-#[derive(Clone)]
-enum FileSystemType {
-    File,
-    Directory,
-}
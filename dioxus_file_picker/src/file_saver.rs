@@ -0,0 +1,203 @@
+use std::path::PathBuf;
+
+use dioxus::prelude::*;
+
+use crate::{
+    Accept, ExplorerOptions, FileExplorer, FileSystemType, SaveTarget, SelectionKind,
+    explorer::use_listing,
+    file_picker::{LoadingIndicator, NavigationControls, Stylesheets},
+    save_target::enforce_extension,
+};
+
+/// A file saver component that works on desktop and mobile. The user navigates to a directory and types a file name.
+#[component]
+pub(crate) fn FileSaver(
+    /// The initial file name
+    default_name: Option<String>,
+    /// If Some, appended to the file name unless it already ends with it
+    extension: Option<String>,
+    /// If true, the user is asked to confirm before an existing file is chosen
+    #[props(default = true)]
+    confirm_overwrite: bool,
+    open_at: Option<PathBuf>,
+    on_submit: Callback<SaveTarget, ()>,
+    /// If Some, a cancel button is shown which calls it
+    on_cancel: Option<Callback<(), ()>>,
+) -> Element {
+    let mut explorer = use_signal(|| {
+        let accept = match extension.as_deref() {
+            Some(extension) => Accept::new().extension(extension),
+            None => Accept::new(),
+        };
+        FileExplorer::new(ExplorerOptions {
+            open_at,
+            accept,
+            selection: SelectionKind::Files,
+            multiple: false,
+            ..Default::default()
+        })
+    });
+    use_listing(explorer);
+    let mut file_name = use_signal(|| default_name.unwrap_or_default());
+    // The existing file the user has to confirm replacing
+    let mut pending_overwrite: Signal<Option<PathBuf>> = use_signal(|| None);
+    let mut name_error: Signal<Option<String>> = use_signal(|| None);
+
+    let save = use_callback({
+        let extension = extension.clone();
+        move |confirmed: bool| {
+            let name = file_name.read().trim().to_owned();
+            if name.is_empty() {
+                name_error.set(Some("Enter a file name".to_owned()));
+                return;
+            }
+            let name = enforce_extension(&name, extension.as_deref());
+            if name.contains(std::path::is_separator) || name == ".." {
+                name_error.set(Some(format!("\"{name}\" is not a valid file name")));
+                return;
+            }
+            let target = explorer.read().current().join(&name);
+            if target.is_dir() {
+                name_error.set(Some(format!("\"{name}\" is a directory")));
+                return;
+            }
+            if target.exists() && confirm_overwrite && !confirmed {
+                pending_overwrite.set(Some(target));
+                return;
+            }
+            name_error.set(None);
+            pending_overwrite.set(None);
+            on_submit.call(SaveTarget::native(target));
+        }
+    });
+
+    let reader = explorer.read();
+    rsx! {
+        Stylesheets {}
+        // File Explorer
+        div { class: "flex flex-col h-full bg-white",
+            // File Explorer header
+            div { class: "flex items-center flex-row ",
+                NavigationControls { explorer }
+            }
+            // File Explorer Content
+            div { class: "flex-1 overflow-y-auto",
+                if let Some(err) = reader.error() {
+                    div {
+                        code { "{err}" }
+                        button { onclick: move |_| explorer.write().dismiss_error(), "x" }
+                    }
+                } else {
+                    LoadingIndicator { explorer }
+                    div {
+                        for entity in reader.entries().to_vec() {
+                            {
+                                let name = entity.name().to_owned();
+                                let key = entity.path().display().to_string();
+                                match entity.kind() {
+                                    FileSystemType::File if !entity.is_accepted() => rsx! {
+                                        div {
+                                            key: "{key}",
+                                            class: "fse",
+                                            i { class: "material-icons deactivated", "description" }
+                                            h1 { class: "deactivated", "{name}" }
+                                        }
+                                    },
+                                    FileSystemType::File => rsx! {
+                                        div {
+                                            key: "{key}",
+                                            class: "fse",
+                                            i {
+                                                class: "material-icons",
+                                                onclick: {
+                                                    let name = name.clone();
+                                                    move |_| {
+                                                        pending_overwrite.set(None);
+                                                        file_name.set(name.clone());
+                                                    }
+                                                },
+                                                "description"
+                                            }
+                                            h1 { "{name}" }
+                                        }
+                                    },
+                                    FileSystemType::Directory => rsx! {
+                                        div {
+                                            key: "{key}",
+                                            class: "fse",
+                                            i {
+                                                class: "material-icons ",
+                                                onclick: move |_| {
+                                                    pending_overwrite.set(None);
+                                                    explorer.write().navigate(entity.path());
+                                                },
+                                                "folder"
+                                            }
+                                            h1 { "{name}" }
+                                        }
+                                    },
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            // Footer
+            div { class: "bg-amber-50 p-4 border-t border-gray-300",
+                if let Some(existing) = pending_overwrite.read().as_ref() {
+                    div { class: "fp-row",
+                        span { class: "flex-grow",
+                            "\"{existing.display()}\" already exists. Do you want to replace it?"
+                        }
+                        button {
+                            class: "fp-button-danger px-4 py-2 rounded",
+                            onclick: move |_| save.call(true),
+                            "Replace"
+                        }
+                        button {
+                            class: "border border-gray-300 px-4 py-2 rounded",
+                            onclick: move |_| pending_overwrite.set(None),
+                            "Cancel"
+                        }
+                    }
+                } else {
+                    div { class: "fp-row",
+                        input {
+                            class: "fp-input",
+                            r#type: "text",
+                            placeholder: "File name",
+                            value: "{file_name}",
+                            oninput: move |event| {
+                                name_error.set(None);
+                                file_name.set(event.value());
+                            },
+                            onkeydown: move |event| {
+                                if event.key() == Key::Enter {
+                                    save.call(false);
+                                }
+                            },
+                        }
+                        if let Some(extension) = extension.as_ref() {
+                            span { class: "fp-muted", ".{extension.trim_start_matches('.')}" }
+                        }
+                        if let Some(on_cancel) = on_cancel {
+                            button {
+                                class: "border border-gray-300 px-4 py-2 rounded",
+                                onclick: move |_| on_cancel.call(()),
+                                "Cancel"
+                            }
+                        }
+                        button {
+                            class: "bg-blue-500 text-white px-4 py-2 rounded hover:bg-blue-600",
+                            onclick: move |_| save.call(false),
+                            "Save"
+                        }
+                    }
+                    if let Some(err) = name_error.read().as_ref() {
+                        div { class: "fp-error", "{err}" }
+                    }
+                }
+            }
+        }
+    }
+}
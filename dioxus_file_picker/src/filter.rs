@@ -0,0 +1,384 @@
+use std::ops::Range;
+
+use dioxus::prelude::*;
+use regex::{Regex, RegexBuilder};
+
+use crate::FileExplorer;
+
+/// How the text typed into the filter box is matched against names.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum FilterMode {
+    /// Case-insensitive, anywhere in the name
+    #[default]
+    Substring,
+    /// Case-insensitive, `*`, `?` and `[…]` against the whole name, e.g. `*.rs`
+    Glob,
+    /// As typed, anywhere in the name, e.g. `^img_\d+`
+    Regex,
+}
+
+impl FilterMode {
+    const ALL: [FilterMode; 3] = [FilterMode::Substring, FilterMode::Glob, FilterMode::Regex];
+
+    fn label(self) -> &'static str {
+        match self {
+            FilterMode::Substring => "Contains",
+            FilterMode::Glob => "Glob",
+            FilterMode::Regex => "Regex",
+        }
+    }
+}
+
+/// Narrows the entries shown to those whose name matches. Only what is shown changes, the selection is left alone.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct NameFilter {
+    text: String,
+    mode: FilterMode,
+    /// None while the text is empty or invalid
+    regex: Option<Regex>,
+    error: Option<String>,
+}
+
+impl NameFilter {
+    pub(crate) fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub(crate) fn mode(&self) -> FilterMode {
+        self.mode
+    }
+
+    /// Why the text is not a valid pattern, if it is not.
+    pub(crate) fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Returns true if some entries may be hidden.
+    pub(crate) fn is_active(&self) -> bool {
+        self.regex.is_some()
+    }
+
+    pub(crate) fn set(&mut self, text: String, mode: FilterMode) {
+        let pattern = match mode {
+            FilterMode::Substring => format!("(?i){}", regex::escape(&text)),
+            FilterMode::Glob => glob_to_regex(&text),
+            FilterMode::Regex => text.clone(),
+        };
+        let compiled = (!text.is_empty()).then(|| {
+            RegexBuilder::new(&pattern)
+                // A pathological pattern must not take the ui down
+                .size_limit(1 << 20)
+                .build()
+        });
+        (self.regex, self.error) = match compiled {
+            None => (None, None),
+            Some(Ok(regex)) => (Some(regex), None),
+            Some(Err(err)) => (None, Some(err.to_string())),
+        };
+        self.text = text;
+        self.mode = mode;
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.set(String::new(), self.mode);
+    }
+
+    /// The byte ranges of `name` to highlight, or None if the entry is hidden. Everything matches, without
+    /// highlights, while the filter is inactive.
+    pub(crate) fn matches(&self, name: &str) -> Option<Vec<Range<usize>>> {
+        let Some(regex) = &self.regex else {
+            return Some(Vec::new());
+        };
+        match self.mode {
+            // The literal parts of the glob, wildcards match anything so are not worth highlighting
+            FilterMode::Glob => {
+                let captures = regex.captures(name)?;
+                Some(
+                    captures
+                        .iter()
+                        .skip(1)
+                        .flatten()
+                        .map(|group| group.range())
+                        .filter(|range| !range.is_empty())
+                        .collect(),
+                )
+            }
+            FilterMode::Substring | FilterMode::Regex => {
+                if !regex.is_match(name) {
+                    return None;
+                }
+                Some(
+                    regex
+                        .find_iter(name)
+                        .map(|found| found.range())
+                        .filter(|range| !range.is_empty())
+                        .collect(),
+                )
+            }
+        }
+    }
+}
+
+/// Translates a glob to an anchored, case-insensitive regex, with its literal parts and character classes in groups.
+/// A `[` without a closing `]` is a literal.
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("(?i)^");
+    let mut literal = String::new();
+    let flush = |regex: &mut String, literal: &mut String| {
+        if !literal.is_empty() {
+            regex.push('(');
+            regex.push_str(&regex::escape(literal));
+            regex.push(')');
+            literal.clear();
+        }
+    };
+    let mut chars = glob.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => {
+                flush(&mut regex, &mut literal);
+                regex.push_str(".*");
+            }
+            '?' => {
+                flush(&mut regex, &mut literal);
+                regex.push('.');
+            }
+            '[' => {
+                let rest = chars.as_str();
+                // `]` right after the opening bracket, or its negation, is part of the class
+                let skip = match rest.as_bytes() {
+                    [b'!' | b'^', b']', ..] => 2,
+                    [b']', ..] | [b'!' | b'^', ..] => 1,
+                    _ => 0,
+                };
+                let Some(end) = rest[skip..].find(']').map(|end| end + skip) else {
+                    literal.push(c);
+                    continue;
+                };
+                flush(&mut regex, &mut literal);
+                let (negated, class) = match rest[..end].strip_prefix(['!', '^']) {
+                    Some(class) => (true, class),
+                    None => (false, &rest[..end]),
+                };
+                regex.push_str(if negated { "([^" } else { "([" });
+                // Escapes everything regex classes treat specially, so `--`, `&&` and `~~` are no set operations and
+                // only a `-` between two characters makes a range
+                let push_escaped = |regex: &mut String, c: char| {
+                    if matches!(c, '\\' | '[' | ']' | '&' | '~' | '^' | '-') {
+                        regex.push('\\');
+                    }
+                    regex.push(c);
+                };
+                let mut class = class.chars().peekable();
+                while let Some(c) = class.next() {
+                    push_escaped(&mut regex, c);
+                    if class.peek() == Some(&'-') {
+                        let mut range = class.clone();
+                        range.next();
+                        if let Some(end) = range.next() {
+                            regex.push('-');
+                            push_escaped(&mut regex, end);
+                            class = range;
+                        }
+                    }
+                }
+                regex.push_str("])");
+                chars = rest[end + 1..].chars();
+            }
+            _ => literal.push(c),
+        }
+    }
+    flush(&mut regex, &mut literal);
+    regex.push('$');
+    regex
+}
+
+/// The filter box: the text, how it is matched, and how many entries it lets through.
+#[component]
+pub(crate) fn FilterBox(explorer: Signal<FileExplorer>) -> Element {
+    let reader = explorer.read();
+    let filter = &reader.filter;
+    let mode = filter.mode();
+    let error = filter.error().map(str::to_owned);
+    let shown = filter.is_active().then(|| {
+        let shown = reader
+            .entries()
+            .iter()
+            .filter(|entry| filter.matches(entry.name()).is_some())
+            .count();
+        (shown, reader.entries().len())
+    });
+    let is_empty = filter.text().is_empty();
+    rsx! {
+        div { class: "fp-row fp-filter",
+            i { class: "material-icons", "filter_list" }
+            input {
+                class: "fp-input",
+                class: if error.is_some() { "fp-input-invalid" },
+                r#type: "search",
+                placeholder: "Filter",
+                spellcheck: false,
+                title: error.unwrap_or_default(),
+                value: "{filter.text()}",
+                oninput: move |event| explorer.write().filter.set(event.value(), mode),
+                onkeydown: move |event| {
+                    if event.key() == Key::Escape {
+                        explorer.write().filter.clear();
+                    }
+                },
+            }
+            select {
+                title: "How the filter matches names",
+                onchange: move |event| {
+                    let value = event.value();
+                    if let Some(mode) = FilterMode::ALL.into_iter().find(|mode| mode.label() == value) {
+                        let mut writer = explorer.write();
+                        let text = writer.filter.text().to_owned();
+                        writer.filter.set(text, mode);
+                    }
+                },
+                for option_mode in FilterMode::ALL {
+                    option {
+                        value: option_mode.label(),
+                        selected: option_mode == mode,
+                        {option_mode.label()}
+                    }
+                }
+            }
+            if !is_empty {
+                i {
+                    class: "material-icons",
+                    title: "Clear the filter",
+                    onclick: move |_| explorer.write().filter.clear(),
+                    "close"
+                }
+            }
+            if let Some((shown, total)) = shown {
+                span { class: "fp-filter-count", "{shown} of {total} shown" }
+            }
+        }
+    }
+}
+
+/// `text` with the byte `ranges` highlighted.
+#[component]
+pub(crate) fn Highlighted(text: String, ranges: Vec<Range<usize>>) -> Element {
+    let mut segments = Vec::with_capacity(ranges.len() * 2 + 1);
+    let mut position = 0;
+    for range in &ranges {
+        if range.start < position || range.end > text.len() {
+            continue;
+        }
+        segments.push((&text[position..range.start], false));
+        segments.push((&text[range.clone()], true));
+        position = range.end;
+    }
+    segments.push((&text[position..], false));
+    rsx! {
+        for (segment , is_match) in segments {
+            if is_match {
+                mark { class: "fp-match", "{segment}" }
+            } else {
+                "{segment}"
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+// Ranges are what is highlighted, a single one is meant
+#[allow(clippy::single_range_in_vec_init)]
+mod tests {
+    use super::*;
+
+    fn name_filter(text: &str, mode: FilterMode) -> NameFilter {
+        let mut filter = NameFilter::default();
+        filter.set(text.to_owned(), mode);
+        filter
+    }
+
+    #[test]
+    fn glob_escapes_literals_and_anchors() {
+        assert_eq!(glob_to_regex("*.rs"), r"(?i)^.*(\.rs)$");
+        assert_eq!(glob_to_regex("a?c"), "(?i)^(a).(c)$");
+        assert_eq!(glob_to_regex(""), "(?i)^$");
+    }
+
+    #[test]
+    fn glob_classes() {
+        assert_eq!(glob_to_regex("[abc]"), "(?i)^([abc])$");
+        assert_eq!(glob_to_regex("[!a-z]"), "(?i)^([^a-z])$");
+        assert_eq!(glob_to_regex("[^a]"), "(?i)^([^a])$");
+        assert_eq!(glob_to_regex("[]]"), r"(?i)^([\]])$");
+        assert_eq!(glob_to_regex("[!]]"), r"(?i)^([^\]])$");
+        assert_eq!(glob_to_regex("[&&~]"), r"(?i)^([\&\&\~])$");
+        assert_eq!(glob_to_regex("[!-]"), r"(?i)^([^\-])$");
+    }
+
+    #[test]
+    fn glob_class_dashes_outside_ranges_are_literal() {
+        assert_eq!(glob_to_regex("[a-]"), r"(?i)^([a\-])$");
+        assert_eq!(glob_to_regex("[--]"), r"(?i)^([\-\-])$");
+        assert_eq!(glob_to_regex("[-a]"), r"(?i)^([\-a])$");
+        assert_eq!(glob_to_regex("[+--]"), r"(?i)^([+-\-])$");
+        let filter = name_filter("x[a-]", FilterMode::Glob);
+        assert_eq!(filter.error(), None);
+        assert_eq!(filter.matches("x-"), Some(vec![0..1, 1..2]));
+        assert_eq!(filter.matches("xA"), Some(vec![0..1, 1..2]));
+        assert_eq!(filter.matches("xb"), None);
+        let filter = name_filter("[--]", FilterMode::Glob);
+        assert_eq!(filter.error(), None);
+        assert_eq!(filter.matches("-"), Some(vec![0..1]));
+        assert_eq!(filter.matches("a"), None);
+    }
+
+    #[test]
+    fn glob_unclosed_bracket_is_literal() {
+        assert_eq!(glob_to_regex("a[b"), r"(?i)^(a\[b)$");
+        let filter = name_filter("a[b", FilterMode::Glob);
+        assert_eq!(filter.matches("a[b"), Some(vec![0..3]));
+        assert_eq!(filter.matches("ab"), None);
+    }
+
+    #[test]
+    fn inactive_filter_matches_everything() {
+        let filter = name_filter("", FilterMode::Substring);
+        assert!(!filter.is_active());
+        assert_eq!(filter.matches("anything"), Some(Vec::new()));
+    }
+
+    #[test]
+    fn substring_is_case_insensitive_and_literal() {
+        let filter = name_filter("A.b", FilterMode::Substring);
+        assert_eq!(filter.matches("xa.bya.B"), Some(vec![1..4, 5..8]));
+        assert_eq!(filter.matches("axb"), None);
+    }
+
+    #[test]
+    fn glob_matches_the_whole_name() {
+        let filter = name_filter("*.RS", FilterMode::Glob);
+        assert_eq!(filter.matches("main.rs"), Some(vec![4..7]));
+        assert_eq!(filter.matches("main.rs.bak"), None);
+        let filter = name_filter("img_[0-9]?", FilterMode::Glob);
+        assert_eq!(filter.matches("IMG_12"), Some(vec![0..4, 4..5]));
+        assert_eq!(filter.matches("img_a2"), None);
+    }
+
+    #[test]
+    fn regex_is_case_sensitive() {
+        let filter = name_filter(r"^img_\d+", FilterMode::Regex);
+        assert_eq!(filter.matches("img_042.png"), Some(vec![0..7]));
+        assert_eq!(filter.matches("IMG_042.png"), None);
+    }
+
+    #[test]
+    fn invalid_regex_is_reported_and_inactive() {
+        let mut filter = name_filter("(", FilterMode::Regex);
+        assert!(filter.error().is_some());
+        assert!(!filter.is_active());
+        assert_eq!(filter.matches("name"), Some(Vec::new()));
+        filter.clear();
+        assert_eq!(filter.error(), None);
+        assert_eq!(filter.mode(), FilterMode::Regex);
+    }
+}
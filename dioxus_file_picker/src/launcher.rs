@@ -1,30 +1,11 @@
-use std::{
-    collections::HashSet,
-    env,
-    path::PathBuf,
-    rc::Rc,
-    sync::Arc,
-    time::{self},
-};
-
-#[cfg(any(
-    target_os = "windows",
-    target_os = "macos",
-    target_os = "linux",
-    target_os = "dragonfly",
-    target_os = "freebsd",
-    target_os = "netbsd",
-    target_os = "openbsd"
-))]
-use dioxus::desktop::DesktopService;
-use dioxus::{html::FileEngine, logger::tracing::warn, prelude::*};
-#[cfg(target_arch = "wasm32")]
-use web_sys::{Blob, File};
+use std::path::PathBuf;
 
-#[cfg(not(target_arch = "wasm32"))]
-use crate::file_picker;
+use dioxus::{logger::tracing::error, prelude::*};
 
-use crate::{Overlay, VirtualPaths};
+use crate::{
+    Accept, FileSystem, OverlaySize, PickError, PickOptions, PickOutcome, PickerWindow,
+    SelectionKind, SortOptions, VirtualPaths, use_file_picker,
+};
 
 #[component]
 pub fn FilePickerLauncher(
@@ -36,185 +17,92 @@ pub fn FilePickerLauncher(
     // mobile_native: bool, // todo
     /// Can select multiple
     multiple: bool,
-    /// Can select directories
-    // directory: bool, // todo
-    /// File extensions to accept
-    // accept: Vec<String>, // todo
-    /// If true, can select directories. This has no effect on web and if `desktop_native` is true.
-    // can_accept_directories: bool, // todo
+    /// Whether files, directories or both can be selected. Defaults to files.
+    #[props(default)]
+    selection: SelectionKind,
+    /// The files to accept, e.g. `".csv,image/*"`. Accepts everything if empty. Files that are not accepted are
+    /// greyed out in the integrated file picker.
+    #[props(default, into)]
+    accept: Accept,
     /// The callback to call when a file(s) is selected and submitted. If `multiple` is false, the set may be empty or
     /// contain one.
     on_submit: Callback<VirtualPaths, ()>,
+    /// The callback to call when the user backs out without submitting.
+    on_cancel: Option<Callback<(), ()>>,
+    /// The callback to call when the file picker could not be shown or failed while open.
+    on_error: Option<Callback<PickError, ()>>,
     /// The path to open the file picker at. If null, defaults to current directory. Has no effect on web.
     open_at: Option<PathBuf>,
+    /// The window of the integrated file picker, if it opens in a new window.
+    #[props(default)]
+    window: PickerWindow,
+    /// How much of the window the integrated file picker covers, if it opens in an overlay.
+    #[props(default)]
+    overlay_size: OverlaySize,
+    /// If Some, the picker reopens where the user left it the last time a launcher with the same key was used,
+    /// unless `open_at` is set. On web the browser does so itself, the directories picked from are recorded in
+    /// localStorage.
+    persistence_key: Option<String>,
+    /// What the integrated file picker browses, e.g. an in-memory tree or a backend server. Defaults to the local
+    /// disk. Has no effect on web.
+    #[props(default, into)]
+    file_system: FileSystem,
+    /// If not empty, the only directories the user can browse and pick from, e.g. the project workspace. Has no
+    /// effect on web.
+    #[props(default)]
+    roots: Vec<PathBuf>,
+    /// How the integrated file picker orders entries at first. The user can change it, and with a `persistence_key`
+    /// their choice is remembered.
+    #[props(default)]
+    sort: SortOptions,
     children: Element,
 ) -> Element {
-    // Web
-    #[cfg(target_arch = "wasm32")]
-    {
-        let id = uuid::Uuid::now_v7().to_string();
-        rsx! {
-            input {
-                id: id.clone(),
-                r#type: "file",
-                style: "display: none;",
-                multiple,
-                onchange: move |event: Event<FormData>| {
-                    event.prevent_default();
-                    if let Some(file_engine) = &event.files() {
-                        on_submit.call(VirtualPaths::web(file_engine.clone()));
-                    }
-                },
-            }
-            button {
-                onclick: move |_| {
-                    {
-                        let id = id.clone();
-                        async move {
-                            let _ = document::eval(
-                                    &*format!("document.getElementById('{}').click()", &id),
-                                )
-                                .await
-                                .ok();
-                        }
+    let picker = use_file_picker();
+    let options = PickOptions {
+        desktop_native,
+        desktop_windowed,
+        multiple,
+        accept,
+        selection,
+        open_at,
+        window,
+        overlay_size,
+        persistence_key,
+        file_system,
+        roots,
+        sort,
+    };
+    let on_click = move |_event| {
+        let options = options.clone();
+        async move {
+            match picker.open(options).await {
+                PickOutcome::Selected(paths) => on_submit.call(paths),
+                PickOutcome::Cancelled => {
+                    if let Some(on_cancel) = on_cancel {
+                        on_cancel.call(());
                     }
+                }
+                PickOutcome::Failed(error) => match on_error {
+                    Some(on_error) => on_error.call(error),
+                    None => error!("{error}"),
                 },
-                {children}
             }
         }
-    }
-    // Desktop
-    #[cfg(any(
-        target_os = "windows",
-        target_os = "macos",
-        target_os = "linux",
-        target_os = "dragonfly",
-        target_os = "freebsd",
-        target_os = "netbsd",
-        target_os = "openbsd"
-    ))]
+    };
+    // Web
+    #[cfg(target_arch = "wasm32")]
     {
-        let mut overlay_active = use_signal(|| false);
-        let mut current_opened_window: Signal<Option<Rc<DesktopService>>> = use_signal(|| None);
-        fn close_window(window_signal: &mut Signal<Option<Rc<DesktopService>>>) {
-            if let Some(window) = window_signal.write().take() {
-                window.close();
-            }
+        rsx! {
+            button { onclick: on_click, {children} }
+            {picker.render()}
         }
-        let on_submit = use_callback(move |paths: VirtualPaths| {
-            on_submit.call(paths);
-            overlay_active.set(false);
-            close_window(&mut current_opened_window);
-        });
-        let on_click = move |_event| {
-            let open_at = open_at.clone();
-            async move {
-                async fn create_dioxus_window(
-                    multiple: bool,
-                    on_submit: Callback<VirtualPaths, ()>,
-                    open_at: Option<PathBuf>,
-                    window_signal: &mut Signal<Option<Rc<DesktopService>>>,
-                ) {
-                    let dom = VirtualDom::new_with_props(
-                        file_picker::FilePicker,
-                        file_picker::FilePickerProps {
-                            multiple,
-                            open_at,
-                            on_submit,
-                        },
-                    );
-                    let window = dioxus::desktop::window()
-                        .new_window(
-                            dom,
-                            dioxus::desktop::Config::new().with_menu(None),
-                            // .with_window(dioxus::desktop::WindowBuilder::new().with_decorations(false)),
-                        )
-                        .await;
-                    window_signal.set(Some(window));
-                }
-                let path = open_at.unwrap_or_else(|| {
-                    env::current_dir().expect("Failed to get current directory")
-                });
-                close_window(&mut current_opened_window);
-                if desktop_native {
-                    let start_time = time::Instant::now();
-                    let files;
-                    if multiple {
-                        files = rfd::FileDialog::new()
-                            .set_directory(&path)
-                            .pick_files()
-                            .unwrap_or(Vec::new())
-                    } else {
-                        files = rfd::FileDialog::new()
-                            .set_directory(&path)
-                            .pick_file()
-                            .map(|e| vec![e])
-                            .unwrap_or(Vec::new());
-                    }
-                    let elapsed = start_time.elapsed();
-                    let within_one_second = elapsed <= time::Duration::from_secs(1);
-                    if within_one_second {
-                        debug_assert!(files.is_empty());
-                        warn!(
-                            "Native file dialog closed too quickly. This was likely an error. Launching a dioxus file dialog instead"
-                        );
-                        create_dioxus_window(
-                            multiple,
-                            on_submit,
-                            Some(path),
-                            &mut current_opened_window,
-                        )
-                        .await;
-                    } else {
-                        on_submit.call(VirtualPaths::native(files.into_iter().collect()));
-                    }
-                } else if desktop_windowed {
-                    create_dioxus_window(
-                        multiple,
-                        on_submit,
-                        Some(path),
-                        &mut current_opened_window,
-                    );
-                } else {
-                    overlay_active.set(true);
-                }
-            }
-        };
-        return rsx! {
-            div { onclick: on_click, {children} }
-            if !(desktop_native || desktop_windowed) {
-                Overlay { active: overlay_active,
-                    file_picker::FilePicker { multiple, on_submit }
-                }
-            }
-        };
     }
-    // Mobile/fallback
-    #[cfg(not(any(
-        target_arch = "wasm32",
-        //
-        target_os = "windows",
-        target_os = "macos",
-        target_os = "linux",
-        target_os = "dragonfly",
-        target_os = "freebsd",
-        target_os = "netbsd",
-        target_os = "openbsd"
-    )))]
+    // Desktop/mobile
+    #[cfg(not(target_arch = "wasm32"))]
     {
-        let mut overlay_active = use_signal(|| false);
-        let on_submit = use_callback(move |paths: VirtualPaths| {
-            on_submit.call(paths);
-            overlay_active.set(false);
-        });
-        let on_click = move |_event| {
-            overlay_active.set(true);
-        };
-        return rsx! {
+        rsx! {
             div { onclick: on_click, {children} }
-            Overlay { active: overlay_active,
-                file_picker::FilePicker { multiple, on_submit }
-            }
-        };
+            {picker.render()}
+        }
     }
 }
@@ -1,11 +1,64 @@
 #![allow(unused_imports)]
-
 #![doc = include_str!("../README.md")]
 
 #[cfg(not(target_arch = "wasm32"))]
 mod file_picker;
 #[cfg(not(target_arch = "wasm32"))]
 pub use file_picker::*;
+#[cfg(not(target_arch = "wasm32"))]
+mod content_search;
+#[cfg(not(target_arch = "wasm32"))]
+mod details;
+#[cfg(not(target_arch = "wasm32"))]
+mod explorer;
+#[cfg(not(target_arch = "wasm32"))]
+mod file_saver;
+#[cfg(not(target_arch = "wasm32"))]
+mod filter;
+#[cfg(not(target_arch = "wasm32"))]
+mod path_bar;
+#[cfg(not(target_arch = "wasm32"))]
+mod places;
+#[cfg(not(target_arch = "wasm32"))]
+mod preview;
+#[cfg(not(target_arch = "wasm32"))]
+mod quick_open;
+#[cfg(not(target_arch = "wasm32"))]
+mod thumbnails;
+#[cfg(not(target_arch = "wasm32"))]
+mod walk;
+#[cfg(not(target_arch = "wasm32"))]
+pub use explorer::*;
+#[cfg(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+mod native_dialog;
+
+mod accept;
+pub use accept::*;
+
+mod backend;
+pub use backend::*;
+
+mod provider;
+pub use provider::*;
+
+mod picker_window;
+pub use picker_window::*;
+
+mod session;
+
+mod sort;
+pub use sort::*;
+
+mod selection_kind;
+pub use selection_kind::*;
 
 mod overlay;
 pub use overlay::*;
@@ -13,5 +66,17 @@ pub use overlay::*;
 mod launcher;
 pub use launcher::*;
 
+mod use_file_picker;
+pub use use_file_picker::*;
+
+mod drop_zone;
+pub use drop_zone::*;
+
+mod saver_launcher;
+pub use saver_launcher::*;
+
 mod virtual_paths;
 pub use virtual_paths::*;
+
+mod save_target;
+pub use save_target::*;
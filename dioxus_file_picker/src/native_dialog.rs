@@ -0,0 +1,148 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use rfd::{
+    AsyncFileDialog, AsyncMessageDialog, FileHandle, MessageButtons, MessageDialogResult,
+    MessageLevel,
+};
+
+use crate::{Accept, SelectionKind};
+
+static NATIVE_DIALOG_OPEN: AtomicBool = AtomicBool::new(false);
+
+/// Held while a native dialog is open. Only one native dialog is shown at a time, so launchers can not stack dialogs
+/// that wait on each other.
+pub(crate) struct NativeDialogGuard(());
+
+impl NativeDialogGuard {
+    /// Returns None if another native dialog is open.
+    pub(crate) fn acquire() -> Option<Self> {
+        NATIVE_DIALOG_OPEN
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .ok()
+            .map(|_| Self(()))
+    }
+}
+
+impl Drop for NativeDialogGuard {
+    fn drop(&mut self) {
+        NATIVE_DIALOG_OPEN.store(false, Ordering::Release);
+    }
+}
+
+/// Adds a filter for every entry of `accept`, preceded by one combining all of them.
+pub(crate) fn add_accept_filters(mut dialog: AsyncFileDialog, accept: &Accept) -> AsyncFileDialog {
+    for (name, extensions) in accept_filters(accept) {
+        dialog = dialog.add_filter(name, &extensions);
+    }
+    dialog
+}
+
+/// The filters for `accept`, by name. Native dialogs only understand extensions, so MIME types are expanded to their
+/// known extensions. Entries without any are left out, an empty filter would hide every file.
+fn accept_filters(accept: &Accept) -> Vec<(String, Vec<String>)> {
+    if accept.is_any() {
+        return Vec::new();
+    }
+    let filters: Vec<(String, Vec<String>)> = accept
+        .entries()
+        .iter()
+        .map(|entry| (entry.to_string(), entry.extensions()))
+        .filter(|(_, extensions)| !extensions.is_empty())
+        .collect();
+    if filters.len() <= 1 {
+        return filters;
+    }
+    let names: Vec<&str> = filters.iter().map(|(name, _)| name.as_str()).collect();
+    let combined = (
+        names.join(","),
+        filters
+            .iter()
+            .flat_map(|(_, extensions)| extensions.clone())
+            .collect(),
+    );
+    std::iter::once(combined).chain(filters).collect()
+}
+
+/// Shows `dialog` without blocking the event loop. Returns None if the user cancelled.
+pub(crate) async fn pick(
+    dialog: AsyncFileDialog,
+    selection: SelectionKind,
+    multiple: bool,
+) -> Option<Vec<PathBuf>> {
+    let handles = match (selection == SelectionKind::Directories, multiple) {
+        (true, true) => dialog.pick_folders().await,
+        (true, false) => dialog.pick_folder().await.map(|e| vec![e]),
+        (false, true) => dialog.pick_files().await,
+        (false, false) => dialog.pick_file().await.map(|e| vec![e]),
+    };
+    handles.map(|handles| handles.iter().map(into_path).collect())
+}
+
+/// Shows `dialog` as a save dialog without blocking the event loop. Returns None if the user cancelled.
+pub(crate) async fn save(dialog: AsyncFileDialog) -> Option<PathBuf> {
+    dialog.save_file().await.as_ref().map(into_path)
+}
+
+/// Asks whether the existing file at `path` should be replaced, for paths the save dialog itself did not confirm.
+pub(crate) async fn confirm_replace(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .unwrap_or(path.as_os_str())
+        .to_string_lossy();
+    let result = AsyncMessageDialog::new()
+        .set_level(MessageLevel::Warning)
+        .set_title("Replace file?")
+        .set_description(format!("{name} already exists. Do you want to replace it?"))
+        .set_buttons(MessageButtons::YesNo)
+        .show()
+        .await;
+    result == MessageDialogResult::Yes
+}
+
+fn into_path(handle: &FileHandle) -> PathBuf {
+    handle.path().to_path_buf()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filters(spec: &str) -> Vec<(String, Vec<String>)> {
+        accept_filters(&Accept::parse(spec))
+    }
+
+    #[test]
+    fn everything_needs_no_filter() {
+        assert!(filters("").is_empty());
+        assert!(filters("*/*,.csv").is_empty());
+    }
+
+    #[test]
+    fn a_single_entry_has_no_combined_filter() {
+        assert_eq!(
+            filters(".CSV"),
+            [(".csv".to_owned(), vec!["csv".to_owned()])]
+        );
+    }
+
+    #[test]
+    fn several_entries_are_combined_first() {
+        let filters = filters(".csv,text/tab-separated-values");
+        assert_eq!(filters.len(), 3);
+        assert_eq!(filters[0].0, ".csv,text/tab-separated-values");
+        assert!(filters[0].1.contains(&"csv".to_owned()));
+        assert!(filters[0].1.contains(&"tsv".to_owned()));
+    }
+
+    #[test]
+    fn mime_types_without_extensions_are_left_out() {
+        assert!(filters("application/x-unknown-to-anyone").is_empty());
+        assert_eq!(
+            filters(".csv,application/x-unknown-to-anyone"),
+            [(".csv".to_owned(), vec!["csv".to_owned()])]
+        );
+    }
+}
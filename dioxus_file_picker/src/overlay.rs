@@ -1,14 +1,81 @@
-use std::ops::Deref;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use dioxus::prelude::*;
 
+/// How much of the window the overlay file picker covers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverlaySize {
+    /// Covers the whole window.
+    #[default]
+    FullScreen,
+    /// A centred panel over a dimmed backdrop. Clicking the backdrop dismisses it.
+    Panel,
+}
+
+/// A modal dialog over the current window. It is shown with `showModal`, so it renders in the browser's top layer
+/// above everything else, no matter the stacking contexts or overflow of its parents, and the rest of the page takes
+/// no input while it is open. Escape or a click on the backdrop calls `on_dismiss`, the dialog stays open until
+/// `active` is false. On close, focus goes back to the element that had it when the dialog opened.
 #[component]
-pub(crate) fn Overlay(active: ReadOnlySignal<bool>, children: Element) -> Element {
+pub(crate) fn Overlay(
+    active: ReadOnlySignal<bool>,
+    #[props(default)] size: OverlaySize,
+    on_dismiss: Option<Callback<(), ()>>,
+    children: Element,
+) -> Element {
+    static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+    let id = use_hook(|| {
+        format!(
+            "dioxus-file-picker-overlay-{}",
+            NEXT_ID.fetch_add(1, Ordering::Relaxed)
+        )
+    });
+    let effect_id = id.clone();
+    use_effect(move || {
+        let script = if *active.read() {
+            format!(
+                "const dialog = document.getElementById({effect_id:?});
+                if (dialog && !dialog.open) {{
+                    dialog.fpTrigger = document.activeElement;
+                    dialog.showModal();
+                }}"
+            )
+        } else {
+            format!(
+                "const dialog = document.getElementById({effect_id:?});
+                if (dialog && dialog.open) {{
+                    dialog.close();
+                    dialog.fpTrigger?.focus?.();
+                    dialog.fpTrigger = null;
+                }}"
+            )
+        };
+        document::eval(&script);
+    });
+    let dismiss = move || {
+        if let Some(on_dismiss) = on_dismiss {
+            on_dismiss.call(());
+        }
+    };
     rsx! {
-        div {
-            style: "position: fixed; top: 0; left: 0; width: 100%; height: 100%; z-index: 1000;",
-            style: if !*active.read() { "display: none;" },
-            {children}
+        dialog {
+            id,
+            class: match size {
+                OverlaySize::FullScreen => "fp-overlay fp-overlay-full",
+                OverlaySize::Panel => "fp-overlay fp-overlay-panel",
+            },
+            // Escape, the dialog is closed by `active` rather than by the browser
+            oncancel: move |event| {
+                event.prevent_default();
+                dismiss();
+            },
+            // Only clicks on the backdrop reach the dialog itself, the content stops the rest
+            onclick: move |_| dismiss(),
+            div {
+                class: "fp-overlay-content",
+                onclick: move |event| event.stop_propagation(),
+                {children}
+            }
         }
     }
 }
@@ -0,0 +1,419 @@
+use std::{
+    env,
+    path::{self, PathBuf},
+};
+
+use dioxus::prelude::*;
+
+use crate::{FileExplorer, FileSystem, FileSystemType, explorer::is_within_roots, places};
+
+/// The most suggestions shown under the location field
+const MAX_SUGGESTIONS: usize = 50;
+
+/// What of the explorer a typed location is resolved against, so it can be done on another thread.
+struct Location {
+    file_system: FileSystem,
+    /// None while the roots are listed
+    current: Option<PathBuf>,
+    roots: Vec<PathBuf>,
+}
+
+impl Location {
+    fn of(explorer: &FileExplorer) -> Self {
+        Self {
+            file_system: explorer.file_system().clone(),
+            current: (!explorer.is_listing_roots()).then(|| explorer.current().to_path_buf()),
+            roots: explorer.roots().to_vec(),
+        }
+    }
+}
+
+/// The current directory as breadcrumbs, one per ancestor, which can be switched to an editable location field.
+#[component]
+pub(crate) fn PathBar(explorer: Signal<FileExplorer>) -> Element {
+    let mut editing = use_signal(|| false);
+    let mut text = use_signal(String::new);
+    // What was typed last, None until something is
+    let mut typed: Signal<Option<String>> = use_signal(|| None);
+    let mut error: Signal<Option<String>> = use_signal(|| None);
+
+    let mut start_editing = move || {
+        let reader = explorer.read();
+        let current = if reader.is_listing_roots() {
+            String::new()
+        } else {
+            with_trailing_separator(&reader.current().display().to_string())
+        };
+        drop(reader);
+        text.set(current);
+        typed.set(None);
+        error.set(None);
+        editing.set(true);
+    };
+    let mut stop_editing = move || {
+        editing.set(false);
+        typed.set(None);
+        error.set(None);
+    };
+    // Directories are read on another thread, each keystroke drops the lookup of the one before
+    let completions = use_resource(move || async move {
+        let input = typed()?;
+        let location = Location::of(&explorer.peek());
+        let names = tokio::task::spawn_blocking({
+            let input = input.clone();
+            move || complete(&location, &input)
+        })
+        .await
+        .ok()?;
+        Some((input, names))
+    });
+    // Only the suggestions for what is typed now, so Tab never completes with those of an earlier input
+    let suggestions = move || match &*completions.read() {
+        Some(Some((input, names))) if Some(input) == typed.read().as_ref() => names.clone(),
+        _ => Vec::new(),
+    };
+    let mut update_text = move |value: String| {
+        typed.set(Some(value.clone()));
+        error.set(None);
+        text.set(value);
+    };
+    let mut submit = move || match open_location(&mut explorer.write(), &text.read()) {
+        Ok(()) => stop_editing(),
+        Err(message) => error.set(Some(message)),
+    };
+
+    if !editing() {
+        let reader = explorer.read();
+        let crumbs = breadcrumbs(&reader);
+        let is_listing_roots = reader.is_listing_roots();
+        return rsx! {
+            div { class: "border border-white-500 p-4 rounded-md flex-grow overflow-x-auto fp-path-bar",
+                if is_listing_roots {
+                    span { class: "fp-crumb", "Locations" }
+                }
+                for (index , (name , path)) in crumbs.into_iter().enumerate() {
+                    if index > 0 {
+                        span { class: "fp-crumb-separator", "›" }
+                    }
+                    span {
+                        class: "fp-crumb",
+                        title: "{path.display()}",
+                        onclick: move |_| {
+                            if explorer.read().current() != path {
+                                explorer.write().navigate(path.clone());
+                            }
+                        },
+                        "{name}"
+                    }
+                }
+                i {
+                    class: "material-icons fp-path-bar-edit",
+                    title: "Type a location",
+                    onclick: move |_| start_editing(),
+                    "edit"
+                }
+            }
+        };
+    }
+
+    rsx! {
+        div { class: "flex-grow fp-path-field",
+            div { class: "fp-row",
+                input {
+                    class: "fp-input",
+                    r#type: "text",
+                    value: "{text}",
+                    spellcheck: false,
+                    placeholder: "Type a path, ~/… or file://…",
+                    onmounted: move |event| async move {
+                        let _ = event.set_focus(true).await;
+                    },
+                    oninput: move |event| update_text(event.value()),
+                    onkeydown: move |event| match event.key() {
+                        Key::Enter => submit(),
+                        Key::Escape => stop_editing(),
+                        Key::Tab => {
+                            event.prevent_default();
+                            let completed = complete_common_prefix(&text.read(), &suggestions());
+                            if let Some(completed) = completed {
+                                update_text(completed);
+                            }
+                        }
+                        _ => {}
+                    },
+                }
+                i {
+                    class: "material-icons",
+                    title: "Go",
+                    onclick: move |_| submit(),
+                    "arrow_forward"
+                }
+                i {
+                    class: "material-icons",
+                    title: "Show breadcrumbs",
+                    onclick: move |_| stop_editing(),
+                    "close"
+                }
+            }
+            if let Some(error) = error() {
+                div { class: "fp-error fp-path-error", "{error}" }
+            }
+            if !suggestions().is_empty() {
+                div { class: "fp-suggestions",
+                    for name in suggestions() {
+                        div {
+                            key: "{name}",
+                            class: "fp-suggestion",
+                            onclick: move |_| {
+                                let completed = with_trailing_separator(&replace_last_segment(&text.read(), &name));
+                                update_text(completed);
+                            },
+                            "{name}"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The ancestors of the current directory that can be opened, top first, with their names. Stops at the root the
+/// explorer is in, if it has roots.
+fn breadcrumbs(explorer: &FileExplorer) -> Vec<(String, PathBuf)> {
+    if explorer.is_listing_roots() {
+        return Vec::new();
+    }
+    let file_system = explorer.file_system();
+    let mut crumbs = Vec::new();
+    let mut next = Some(explorer.current().to_path_buf());
+    while let Some(path) = next.take() {
+        if !explorer.is_allowed(&path) {
+            break;
+        }
+        let name = match path.file_name() {
+            Some(name) => name.to_string_lossy().into_owned(),
+            None => path.display().to_string(),
+        };
+        next = file_system.parent(&path);
+        crumbs.push((name, path));
+    }
+    crumbs.reverse();
+    crumbs
+}
+
+/// Opens what was typed into the location field: a directory is navigated to, a file is revealed and selected.
+fn open_location(explorer: &mut FileExplorer, input: &str) -> Result<(), String> {
+    let path = resolve(&Location::of(explorer), input)?;
+    let file_system = explorer.file_system().clone();
+    let metadata = file_system
+        .metadata(&path)
+        .map_err(|err| format!("{}: {err}", path.display()))?;
+    if !explorer.is_allowed(&path) {
+        return Err(format!(
+            "{} is outside the allowed directories",
+            path.display()
+        ));
+    }
+    match metadata.kind {
+        FileSystemType::Directory => {
+            explorer.navigate(path);
+            Ok(())
+        }
+        FileSystemType::File => {
+            let entry = explorer
+                .reveal(&path)
+                .ok_or_else(|| format!("{} could not be opened", path.display()))?;
+            if !explorer.select(&entry) {
+                return Err(format!("{} can not be selected", entry.name()));
+            }
+            explorer.is_selecting = true;
+            Ok(())
+        }
+    }
+}
+
+/// Turns the typed text into an absolute path. `file://` uris, `~` and `$VAR` or `${VAR}` are expanded when the local
+/// disk is browsed, relative paths are relative to the current directory.
+fn resolve(location: &Location, input: &str) -> Result<PathBuf, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err("Type a path".to_owned());
+    }
+    let file_system = &location.file_system;
+    let path = if file_system.is_local() {
+        match input.strip_prefix("file://") {
+            Some(_) => places::file_uri_to_path(input)
+                .ok_or_else(|| format!("{input} is not a local file uri"))?,
+            None => PathBuf::from(expand(input)?),
+        }
+    } else {
+        PathBuf::from(input)
+    };
+    let path = match &location.current {
+        Some(current) if !path.is_absolute() => current.join(path),
+        _ => path,
+    };
+    file_system
+        .absolute(&path)
+        .map_err(|err| format!("{}: {err}", path.display()))
+}
+
+/// Expands a leading `~` to the home directory and `$VAR` or `${VAR}` to the variable's value.
+fn expand(input: &str) -> Result<String, String> {
+    let input = match input.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with(path::is_separator) => {
+            let home = dirs::home_dir().ok_or("There is no home directory")?;
+            format!("{}{rest}", home.display())
+        }
+        _ => input.to_owned(),
+    };
+    let mut expanded = String::with_capacity(input.len());
+    let mut rest = input.as_str();
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let (name, remaining) = match after.strip_prefix('{') {
+            Some(braced) => {
+                let end = braced.find('}').ok_or("Unclosed ${")?;
+                (&braced[..end], &braced[end + 1..])
+            }
+            None => {
+                let end = after
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(after.len());
+                (&after[..end], &after[end..])
+            }
+        };
+        if name.is_empty() {
+            // A lone `$`, e.g. in a file name
+            expanded.push('$');
+        } else {
+            let value = env::var(name).map_err(|_| format!("${name} is not set"))?;
+            expanded.push_str(&value);
+        }
+        rest = remaining;
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// The names of the directories the last segment of `input` could be completed to. Hidden directories are only
+/// suggested once a `.` is typed.
+fn complete(location: &Location, input: &str) -> Vec<String> {
+    let (dir, prefix) = match input.rfind(path::is_separator) {
+        Some(index) => (&input[..=index], &input[index + 1..]),
+        None => return Vec::new(),
+    };
+    let Ok(dir) = resolve(location, dir) else {
+        return Vec::new();
+    };
+    let Ok(entries) = location.file_system.read_dir(&dir) else {
+        return Vec::new();
+    };
+    let prefix = prefix.to_lowercase();
+    let mut names: Vec<String> = entries
+        .flatten()
+        .filter(|entry| {
+            entry.metadata.kind == FileSystemType::Directory
+                && is_within_roots(&location.roots, &entry.path)
+        })
+        .map(|entry| entry.name)
+        .filter(|name| name.to_lowercase().starts_with(&prefix))
+        .filter(|name| prefix.starts_with('.') || !name.starts_with('.'))
+        .collect();
+    // Sorted first, so the suggestions kept are the first ones and not whichever were read first
+    names.sort_by_key(|name| name.to_lowercase());
+    names.truncate(MAX_SUGGESTIONS);
+    names
+}
+
+/// What Tab completes `input` to: the only suggestion as a directory, or the prefix all suggestions share.
+fn complete_common_prefix(input: &str, suggestions: &[String]) -> Option<String> {
+    match suggestions {
+        [] => None,
+        [only] => Some(with_trailing_separator(&replace_last_segment(input, only))),
+        [first, rest @ ..] => {
+            let common = rest.iter().fold(first.as_str(), |common, name| {
+                let length = common
+                    .char_indices()
+                    .zip(name.chars())
+                    .take_while(|((_, a), b)| a.to_lowercase().eq(b.to_lowercase()))
+                    .last()
+                    .map_or(0, |((index, a), _)| index + a.len_utf8());
+                &common[..length]
+            });
+            Some(replace_last_segment(input, common))
+        }
+    }
+}
+
+fn replace_last_segment(input: &str, segment: &str) -> String {
+    match input.rfind(path::is_separator) {
+        Some(index) => format!("{}{segment}", &input[..=index]),
+        None => segment.to_owned(),
+    }
+}
+
+fn with_trailing_separator(path: &str) -> String {
+    if path.ends_with(path::is_separator) {
+        path.to_owned()
+    } else {
+        format!("{path}{}", path::MAIN_SEPARATOR)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::MAIN_SEPARATOR;
+
+    use super::*;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| (*name).to_owned()).collect()
+    }
+
+    #[test]
+    fn nothing_to_complete_without_suggestions() {
+        assert_eq!(complete_common_prefix("/home/us", &[]), None);
+    }
+
+    #[test]
+    fn the_only_suggestion_is_completed_as_a_directory() {
+        assert_eq!(
+            complete_common_prefix("/home/us", &names(&["user"])),
+            Some(format!("/home/user{MAIN_SEPARATOR}"))
+        );
+    }
+
+    #[test]
+    fn several_suggestions_complete_to_their_common_prefix() {
+        assert_eq!(
+            complete_common_prefix("/home/d", &names(&["Documents", "Downloads"])),
+            Some("/home/Do".to_owned())
+        );
+        assert_eq!(
+            complete_common_prefix("/home/", &names(&["music", "pictures"])),
+            Some("/home/".to_owned())
+        );
+    }
+
+    #[test]
+    fn the_common_prefix_ignores_case_and_keeps_the_first_spelling() {
+        assert_eq!(
+            complete_common_prefix("/srv/", &names(&["Projects", "projects-old"])),
+            Some("/srv/Projects".to_owned())
+        );
+        assert_eq!(
+            complete_common_prefix("/srv/", &names(&["Ärger", "ärgerlich"])),
+            Some("/srv/Ärger".to_owned())
+        );
+    }
+
+    #[test]
+    fn input_without_a_separator_is_completed_whole() {
+        assert_eq!(
+            complete_common_prefix("d", &names(&["docs", "dotfiles"])),
+            Some("do".to_owned())
+        );
+    }
+}
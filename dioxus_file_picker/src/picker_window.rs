@@ -0,0 +1,434 @@
+use std::{fmt, rc::Rc};
+
+#[cfg(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+use dioxus::desktop::{
+    Config, DesktopService, WindowBuilder,
+    tao::dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize, Position, Size},
+};
+
+/// How the window of the windowed integrated picker looks and behaves. Only used on desktop when the picker opens in
+/// a new window.
+///
+/// Everything not set keeps the crate's defaults: a title fitting the dialog, 800x600, at least 400x300, modal for
+/// the launching window, and the size and position the user last gave the window.
+#[derive(Clone, Default)]
+pub struct PickerWindow {
+    title: Option<String>,
+    size: Option<(f64, f64)>,
+    position: Option<(f64, f64)>,
+    min_size: Option<(f64, f64)>,
+    always_on_top: bool,
+    not_modal: bool,
+    forget_geometry: bool,
+    #[cfg(any(
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    customize: Option<Rc<dyn Fn(WindowBuilder) -> WindowBuilder>>,
+}
+
+impl PickerWindow {
+    /// Keeps all the defaults, see [PickerWindow].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The window title.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// The initial size in logical pixels.
+    pub fn size(mut self, width: f64, height: f64) -> Self {
+        self.size = Some((width, height));
+        self
+    }
+
+    /// The initial position of the window's top left corner in logical pixels.
+    pub fn position(mut self, x: f64, y: f64) -> Self {
+        self.position = Some((x, y));
+        self
+    }
+
+    /// The minimum size in logical pixels.
+    pub fn min_size(mut self, width: f64, height: f64) -> Self {
+        self.min_size = Some((width, height));
+        self
+    }
+
+    /// If true, the window stays above all other windows.
+    pub fn always_on_top(mut self, always_on_top: bool) -> Self {
+        self.always_on_top = always_on_top;
+        self
+    }
+
+    /// If true, the default, the window belongs to the window that launched it: it stays above it and, on Windows,
+    /// Linux and the BSDs, the launching window does not take input until the picker closes. On macOS the launching
+    /// window keeps taking input.
+    pub fn modal(mut self, modal: bool) -> Self {
+        self.not_modal = !modal;
+        self
+    }
+
+    /// If true, the default, the size and position the user last gave the window are reused when the same launcher
+    /// opens it again. Takes precedence over [PickerWindow::size] and [PickerWindow::position].
+    pub fn remember_geometry(mut self, remember: bool) -> Self {
+        self.forget_geometry = !remember;
+        self
+    }
+
+    /// Applies `customize` to the window builder after everything else, for options not covered here.
+    #[cfg(any(
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    pub fn customize(
+        mut self,
+        customize: impl Fn(WindowBuilder) -> WindowBuilder + 'static,
+    ) -> Self {
+        self.customize = Some(Rc::new(customize));
+        self
+    }
+
+    pub(crate) fn is_modal(&self) -> bool {
+        !self.not_modal
+    }
+
+    /// The desktop config for a picker window launched from `parent`.
+    #[cfg(any(
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    pub(crate) fn config(
+        &self,
+        default_title: &str,
+        remembered: Option<WindowGeometry>,
+        parent: &DesktopService,
+    ) -> Config {
+        let geometry = self.initial_geometry(remembered);
+        let mut builder = WindowBuilder::new()
+            .with_title(self.title.as_deref().unwrap_or(default_title))
+            .with_inner_size(geometry.size)
+            .with_min_inner_size(geometry.min_size)
+            .with_always_on_top(self.always_on_top);
+        if let Some(position) = geometry.position {
+            builder = builder.with_position(position);
+        }
+        if self.is_modal() {
+            builder = with_owner(builder, parent);
+        }
+        if let Some(customize) = self.customize.as_ref() {
+            builder = customize(builder);
+        }
+        Config::new().with_menu(None).with_window(builder)
+    }
+
+    /// Where and how large a new window starts, the geometry the user last gave it winning over the configured one.
+    #[cfg(any(
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    fn initial_geometry(&self, remembered: Option<WindowGeometry>) -> InitialGeometry {
+        let (width, height) = self.size.unwrap_or((800.0, 600.0));
+        let (min_width, min_height) = self.min_size.unwrap_or((400.0, 300.0));
+        let mut geometry = InitialGeometry {
+            size: LogicalSize::new(width, height).into(),
+            min_size: LogicalSize::new(min_width, min_height).into(),
+            position: self
+                .position
+                .map(|(x, y)| LogicalPosition::new(x, y).into()),
+        };
+        if let Some(remembered) = remembered.filter(|_| !self.forget_geometry) {
+            geometry.size = remembered.size.into();
+            if let Some(position) = remembered.position {
+                geometry.position = Some(position.into());
+            }
+        }
+        geometry
+    }
+}
+
+#[cfg(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+#[derive(Debug, PartialEq)]
+struct InitialGeometry {
+    size: Size,
+    min_size: Size,
+    position: Option<Position>,
+}
+
+/// Makes the window being built belong to `parent`, so it stays above it.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+fn with_owner(builder: WindowBuilder, parent: &DesktopService) -> WindowBuilder {
+    use dioxus::desktop::tao::platform::unix::{WindowBuilderExtUnix, WindowExtUnix};
+    builder.with_transient_for(parent.window.gtk_window())
+}
+
+/// Makes the window being built belong to `parent`, so it stays above it.
+#[cfg(target_os = "windows")]
+fn with_owner(builder: WindowBuilder, parent: &DesktopService) -> WindowBuilder {
+    use dioxus::desktop::tao::platform::windows::{WindowBuilderExtWindows, WindowExtWindows};
+    builder.with_owner_window(parent.window.hwnd())
+}
+
+/// Makes the window being built belong to `parent`, so it stays above it.
+#[cfg(target_os = "macos")]
+fn with_owner(builder: WindowBuilder, parent: &DesktopService) -> WindowBuilder {
+    use dioxus::desktop::tao::platform::macos::{WindowBuilderExtMacOS, WindowExtMacOS};
+    builder.with_parent_window(parent.window.ns_window())
+}
+
+/// Stops or resumes input to the window that launched a modal picker. Does nothing on macOS, where tao can not disable
+/// a window.
+#[cfg(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+pub(crate) fn set_parent_enabled(parent: &DesktopService, enabled: bool) {
+    #[cfg(target_os = "windows")]
+    {
+        use dioxus::desktop::tao::platform::windows::WindowExtWindows;
+        parent.window.set_enable(enabled);
+    }
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    {
+        use dioxus::desktop::tao::platform::unix::WindowExtUnix;
+        use gtk::prelude::WidgetExt;
+        parent.window.gtk_window().set_sensitive(enabled);
+    }
+    #[cfg(target_os = "macos")]
+    let _ = (parent, enabled);
+}
+
+impl PartialEq for PickerWindow {
+    fn eq(&self, other: &Self) -> bool {
+        #[cfg(any(
+            target_os = "windows",
+            target_os = "macos",
+            target_os = "linux",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        ))]
+        let same_customize = match (&self.customize, &other.customize) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        };
+        #[cfg(not(any(
+            target_os = "windows",
+            target_os = "macos",
+            target_os = "linux",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        )))]
+        let same_customize = true;
+        self.title == other.title
+            && self.size == other.size
+            && self.position == other.position
+            && self.min_size == other.min_size
+            && self.always_on_top == other.always_on_top
+            && self.not_modal == other.not_modal
+            && self.forget_geometry == other.forget_geometry
+            && same_customize
+    }
+}
+
+impl fmt::Debug for PickerWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PickerWindow")
+            .field("title", &self.title)
+            .field("size", &self.size)
+            .field("position", &self.position)
+            .field("min_size", &self.min_size)
+            .field("always_on_top", &self.always_on_top)
+            .field("modal", &self.is_modal())
+            .field("remember_geometry", &!self.forget_geometry)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The size and position of a picker window when it closed.
+#[cfg(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct WindowGeometry {
+    size: PhysicalSize<u32>,
+    /// None on platforms that do not report window positions, e.g. Wayland
+    position: Option<PhysicalPosition<i32>>,
+}
+
+#[cfg(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+impl WindowGeometry {
+    pub(crate) fn of(window: &DesktopService) -> Self {
+        Self {
+            size: window.window.inner_size(),
+            position: window.window.outer_position().ok(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_are_modal_and_remember_geometry() {
+        let window = PickerWindow::new();
+        assert!(window.is_modal());
+        assert!(!window.forget_geometry);
+        assert!(!window.modal(false).is_modal());
+    }
+
+    #[test]
+    fn windows_with_the_same_settings_are_equal() {
+        assert_eq!(
+            PickerWindow::new().title("Pick").size(640.0, 480.0),
+            PickerWindow::new().size(640.0, 480.0).title("Pick")
+        );
+        assert_ne!(PickerWindow::new(), PickerWindow::new().always_on_top(true));
+        assert_ne!(
+            PickerWindow::new().customize(|builder| builder),
+            PickerWindow::new().customize(|builder| builder)
+        );
+    }
+
+    fn remembered(position: Option<(i32, i32)>) -> WindowGeometry {
+        WindowGeometry {
+            size: PhysicalSize::new(1000, 700),
+            position: position.map(|(x, y)| PhysicalPosition::new(x, y)),
+        }
+    }
+
+    #[test]
+    fn without_settings_the_window_has_the_default_size() {
+        assert_eq!(
+            PickerWindow::new().initial_geometry(None),
+            InitialGeometry {
+                size: LogicalSize::new(800.0, 600.0).into(),
+                min_size: LogicalSize::new(400.0, 300.0).into(),
+                position: None,
+            }
+        );
+    }
+
+    #[test]
+    fn configured_geometry_replaces_the_defaults() {
+        let window = PickerWindow::new()
+            .size(640.0, 480.0)
+            .min_size(320.0, 240.0)
+            .position(10.0, 20.0);
+        assert_eq!(
+            window.initial_geometry(None),
+            InitialGeometry {
+                size: LogicalSize::new(640.0, 480.0).into(),
+                min_size: LogicalSize::new(320.0, 240.0).into(),
+                position: Some(LogicalPosition::new(10.0, 20.0).into()),
+            }
+        );
+    }
+
+    #[test]
+    fn remembered_geometry_wins_over_the_configured_one() {
+        let window = PickerWindow::new().size(640.0, 480.0).position(10.0, 20.0);
+        assert_eq!(
+            window.initial_geometry(Some(remembered(Some((30, 40))))),
+            InitialGeometry {
+                size: PhysicalSize::new(1000, 700).into(),
+                min_size: LogicalSize::new(400.0, 300.0).into(),
+                position: Some(PhysicalPosition::new(30, 40).into()),
+            }
+        );
+    }
+
+    #[test]
+    fn an_unknown_remembered_position_keeps_the_configured_one() {
+        let window = PickerWindow::new().position(10.0, 20.0);
+        let geometry = window.initial_geometry(Some(remembered(None)));
+        assert_eq!(geometry.size, PhysicalSize::new(1000, 700).into());
+        assert_eq!(
+            geometry.position,
+            Some(LogicalPosition::new(10.0, 20.0).into())
+        );
+    }
+
+    #[test]
+    fn forgetting_the_geometry_ignores_the_remembered_one() {
+        let window = PickerWindow::new().remember_geometry(false);
+        assert_eq!(
+            window.initial_geometry(Some(remembered(Some((30, 40))))),
+            PickerWindow::new().initial_geometry(None)
+        );
+    }
+}
@@ -0,0 +1,542 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use dioxus::prelude::*;
+
+use crate::FileExplorer;
+
+/// A directory the places sidebar links to.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Place {
+    pub(crate) name: String,
+    /// Canonicalized, so it compares equal to [FileExplorer::current]
+    pub(crate) path: PathBuf,
+    pub(crate) icon: &'static str,
+    pub(crate) section: PlaceSection,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PlaceSection {
+    /// Home and the user directories
+    Places,
+    Bookmarks,
+    /// Mounted volumes
+    Devices,
+}
+
+impl PlaceSection {
+    fn title(self) -> &'static str {
+        match self {
+            PlaceSection::Places => "Places",
+            PlaceSection::Bookmarks => "Bookmarks",
+            PlaceSection::Devices => "Devices",
+        }
+    }
+}
+
+/// The places of the current user. Directories that don't exist are left out, as are duplicates.
+pub(crate) fn load() -> Vec<Place> {
+    let mut places = Vec::new();
+    if let Some(home) = dirs::home_dir() {
+        places.push(place("Home".to_owned(), home, "home", PlaceSection::Places));
+    }
+    places.extend(user_dirs());
+    places.extend(bookmarks());
+    places.extend(volumes());
+    let mut seen = std::collections::HashSet::new();
+    places
+        .into_iter()
+        .flatten()
+        .filter(|place| seen.insert(place.path.clone()))
+        .collect()
+}
+
+fn place(name: String, path: PathBuf, icon: &'static str, section: PlaceSection) -> Option<Place> {
+    let path = fs::canonicalize(path).ok()?;
+    path.is_dir().then_some(Place {
+        name,
+        path,
+        icon,
+        section,
+    })
+}
+
+fn file_name(path: &Path) -> String {
+    match path.file_name() {
+        Some(name) => name.to_string_lossy().into_owned(),
+        None => path.display().to_string(),
+    }
+}
+
+// User dirs
+//************************************************************************//
+
+/// The XDG user directories, in the order file managers show them. Named after the directory, since it is localized.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+fn user_dirs() -> Vec<Option<Place>> {
+    const ORDER: [(&str, &str); 8] = [
+        ("DESKTOP", "desktop_windows"),
+        ("DOCUMENTS", "description"),
+        ("DOWNLOAD", "download"),
+        ("MUSIC", "music_note"),
+        ("PICTURES", "image"),
+        ("VIDEOS", "movie"),
+        ("TEMPLATES", "content_copy"),
+        ("PUBLICSHARE", "folder_shared"),
+    ];
+    let (Some(home), Some(config)) = (dirs::home_dir(), dirs::config_dir()) else {
+        return Vec::new();
+    };
+    let Ok(contents) = fs::read_to_string(config.join("user-dirs.dirs")) else {
+        return Vec::new();
+    };
+    let dirs = parse_user_dirs(&contents, &home);
+    ORDER
+        .iter()
+        .filter_map(|(key, icon)| {
+            let (_, path) = dirs.iter().find(|(name, _)| name == key)?;
+            // Unused dirs are set to home itself
+            if *path == home {
+                return None;
+            }
+            Some(place(
+                file_name(path),
+                path.clone(),
+                icon,
+                PlaceSection::Places,
+            ))
+        })
+        .collect()
+}
+
+/// Parses lines like `XDG_DOWNLOAD_DIR="$HOME/Downloads"` into `("DOWNLOAD", "/home/user/Downloads")`. Only `$HOME`
+/// relative and absolute paths are valid there.
+#[cfg(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+fn parse_user_dirs(contents: &str, home: &Path) -> Vec<(String, PathBuf)> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            let name = key.trim().strip_prefix("XDG_")?.strip_suffix("_DIR")?;
+            let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+            let value = value.replace("\\\"", "\"");
+            let path = match value.strip_prefix("$HOME") {
+                Some(rest) => home.join(rest.trim_start_matches('/')),
+                None if value.starts_with('/') => PathBuf::from(value),
+                None => return None,
+            };
+            Some((name.to_owned(), path))
+        })
+        .collect()
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+)))]
+fn user_dirs() -> Vec<Option<Place>> {
+    [
+        (dirs::desktop_dir(), "desktop_windows"),
+        (dirs::document_dir(), "description"),
+        (dirs::download_dir(), "download"),
+        (dirs::audio_dir(), "music_note"),
+        (dirs::picture_dir(), "image"),
+        (dirs::video_dir(), "movie"),
+        (dirs::public_dir(), "folder_shared"),
+    ]
+    .into_iter()
+    .filter_map(|(path, icon)| {
+        let path = path?;
+        Some(place(file_name(&path), path, icon, PlaceSection::Places))
+    })
+    .collect()
+}
+
+// Bookmarks
+//************************************************************************//
+
+/// The GTK bookmarks, lines of a `file://` uri and an optional label. Bookmarks of other schemes, e.g. `sftp://`, are
+/// not on the local disk and left out.
+fn bookmarks() -> Vec<Option<Place>> {
+    let Some(config) = dirs::config_dir() else {
+        return Vec::new();
+    };
+    let Ok(contents) = fs::read_to_string(config.join("gtk-3.0").join("bookmarks")) else {
+        return Vec::new();
+    };
+    contents
+        .lines()
+        .filter_map(parse_bookmark)
+        .map(|(name, path)| place(name, path, "bookmark", PlaceSection::Bookmarks))
+        .collect()
+}
+
+/// The name and path of a bookmarks line, the label if there is one and the file name otherwise.
+fn parse_bookmark(line: &str) -> Option<(String, PathBuf)> {
+    let (uri, label) = match line.trim().split_once(' ') {
+        Some((uri, label)) => (uri, Some(label.trim())),
+        None => (line.trim(), None),
+    };
+    let path = file_uri_to_path(uri)?;
+    let name = match label {
+        Some(label) if !label.is_empty() => label.to_owned(),
+        _ => file_name(&path),
+    };
+    Some((name, path))
+}
+
+/// Converts a `file://` uri to a path, decoding percent escapes. None for other schemes and remote hosts.
+pub(crate) fn file_uri_to_path(uri: &str) -> Option<PathBuf> {
+    let rest = uri.strip_prefix("file://")?;
+    // `file:///path` or `file://localhost/path`
+    let path = rest.strip_prefix("localhost").unwrap_or(rest);
+    if !path.starts_with('/') {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut input = path.bytes();
+    while let Some(byte) = input.next() {
+        if byte == b'%' {
+            let high = input.next().and_then(|b| (b as char).to_digit(16))?;
+            let low = input.next().and_then(|b| (b as char).to_digit(16))?;
+            bytes.push((high * 16 + low) as u8);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStringExt;
+        Some(PathBuf::from(std::ffi::OsString::from_vec(bytes)))
+    }
+    #[cfg(not(unix))]
+    {
+        // `file:///C:/Users` on Windows
+        let path = String::from_utf8(bytes).ok()?;
+        Some(PathBuf::from(path.strip_prefix('/').unwrap_or(&path)))
+    }
+}
+
+// Volumes
+//************************************************************************//
+
+/// The mounted volumes a user would browse: the root file system, removable media and other disks. Pseudo file
+/// systems and system mounts are left out.
+#[cfg(target_os = "linux")]
+fn volumes() -> Vec<Option<Place>> {
+    let Ok(contents) = fs::read_to_string("/proc/self/mountinfo") else {
+        return Vec::new();
+    };
+    parse_mountinfo(&contents)
+        .into_iter()
+        .filter(|(mount_point, file_system_type)| {
+            !is_pseudo_file_system(file_system_type) && !is_system_mount(mount_point)
+        })
+        .map(|(mount_point, _)| {
+            let name = if mount_point == Path::new("/") {
+                "File System".to_owned()
+            } else {
+                file_name(&mount_point)
+            };
+            place(name, mount_point, "storage", PlaceSection::Devices)
+        })
+        .collect()
+}
+
+/// The mount point and file system type of every line. A line is
+/// `36 35 98:0 /mnt1 /mnt2 rw,noatime master:1 - ext3 /dev/root rw,errors=continue`, where the optional fields before
+/// `-` vary in number.
+#[cfg(target_os = "linux")]
+fn parse_mountinfo(contents: &str) -> Vec<(PathBuf, String)> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let (mount, file_system) = line.split_once(" - ")?;
+            let mount_point = mount.split(' ').nth(4)?;
+            let file_system_type = file_system.split(' ').next()?;
+            Some((
+                PathBuf::from(unescape_mountinfo(mount_point)),
+                file_system_type.to_owned(),
+            ))
+        })
+        .collect()
+}
+
+/// Undoes the octal escapes of spaces, tabs, newlines and backslashes, e.g. `\040` for a space.
+#[cfg(target_os = "linux")]
+fn unescape_mountinfo(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut unescaped = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escape = bytes
+            .get(index + 1..index + 4)
+            .filter(|digits| {
+                bytes[index] == b'\\' && digits.iter().all(|d| (b'0'..=b'7').contains(d))
+            })
+            .and_then(|digits| u8::from_str_radix(std::str::from_utf8(digits).ok()?, 8).ok());
+        match escape {
+            Some(byte) => {
+                unescaped.push(byte);
+                index += 4;
+            }
+            None => {
+                unescaped.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&unescaped).into_owned()
+}
+
+/// File systems that hold no user data. Overlay and squashfs are real ones, e.g. the root of a container or a live
+/// system.
+#[cfg(target_os = "linux")]
+fn is_pseudo_file_system(file_system_type: &str) -> bool {
+    const PSEUDO: [&str; 25] = [
+        "autofs",
+        "binfmt_misc",
+        "bpf",
+        "cgroup",
+        "cgroup2",
+        "configfs",
+        "debugfs",
+        "devpts",
+        "devtmpfs",
+        "efivarfs",
+        "fusectl",
+        "hugetlbfs",
+        "mqueue",
+        "nsfs",
+        "proc",
+        "pstore",
+        "ramfs",
+        "rpc_pipefs",
+        "securityfs",
+        "selinuxfs",
+        "sysfs",
+        "tmpfs",
+        "tracefs",
+        "fuse.gvfsd-fuse",
+        "fuse.portal",
+    ];
+    PSEUDO.contains(&file_system_type)
+}
+
+/// Mounts of the system rather than the user. Removable media under `/run/media` are kept.
+#[cfg(target_os = "linux")]
+fn is_system_mount(mount_point: &Path) -> bool {
+    const SYSTEM: [&str; 8] = [
+        "/proc", "/sys", "/dev", "/run", "/boot", "/snap", "/var", "/tmp",
+    ];
+    !mount_point.starts_with("/run/media")
+        && SYSTEM.iter().any(|system| mount_point.starts_with(system))
+}
+
+#[cfg(target_os = "macos")]
+fn volumes() -> Vec<Option<Place>> {
+    let Ok(entries) = fs::read_dir("/Volumes") else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| {
+            let path = entry.path();
+            place(file_name(&path), path, "storage", PlaceSection::Devices)
+        })
+        .collect()
+}
+
+#[cfg(target_os = "windows")]
+fn volumes() -> Vec<Option<Place>> {
+    (b'A'..=b'Z')
+        .map(|letter| {
+            let drive = format!("{}:\\", letter as char);
+            place(
+                drive.clone(),
+                PathBuf::from(drive),
+                "storage",
+                PlaceSection::Devices,
+            )
+        })
+        .collect()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn volumes() -> Vec<Option<Place>> {
+    vec![place(
+        "File System".to_owned(),
+        PathBuf::from("/"),
+        "storage",
+        PlaceSection::Devices,
+    )]
+}
+
+// Sidebar
+//************************************************************************//
+
+/// Links to [load]ed places, grouped by section. Places outside the explorer's roots are left out, and the sidebar is
+/// empty if the explorer does not browse the local disk.
+#[component]
+pub(crate) fn PlacesSidebar(
+    explorer: Signal<FileExplorer>,
+    places: ReadOnlySignal<Vec<Place>>,
+) -> Element {
+    let reader = explorer.read();
+    if !reader.file_system().is_local() {
+        return rsx! {};
+    }
+    let places: Vec<Place> = places
+        .read()
+        .iter()
+        .filter(|place| reader.is_allowed(&place.path))
+        .cloned()
+        .collect();
+    let current = reader.current().to_path_buf();
+    rsx! {
+        nav { class: "fp-places",
+            for section in [PlaceSection::Places, PlaceSection::Bookmarks, PlaceSection::Devices] {
+                if places.iter().any(|place| place.section == section) {
+                    div { class: "fp-places-heading", "{section.title()}" }
+                    for place in places.iter().filter(|place| place.section == section).cloned() {
+                        div {
+                            key: "{place.path.display()}",
+                            class: if place.path == current { "fp-place fp-place-active" } else { "fp-place" },
+                            title: "{place.path.display()}",
+                            onclick: move |_| explorer.write().navigate(place.path.clone()),
+                            i { class: "material-icons", "{place.icon}" }
+                            span { "{place.name}" }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    #[test]
+    fn user_dirs_are_parsed() {
+        let contents = r#"# This file is written by xdg-user-dirs-update
+XDG_DESKTOP_DIR="$HOME/Desktop"
+XDG_DOWNLOAD_DIR="$HOME"
+XDG_MUSIC_DIR="/mnt/music"
+XDG_VIDEOS_DIR="Videos"
+XDG_PICTURES_DIR="$HOME/My \"Pictures\""
+"#;
+        assert_eq!(
+            parse_user_dirs(contents, Path::new("/home/user")),
+            vec![
+                ("DESKTOP".to_owned(), PathBuf::from("/home/user/Desktop")),
+                ("DOWNLOAD".to_owned(), PathBuf::from("/home/user")),
+                ("MUSIC".to_owned(), PathBuf::from("/mnt/music")),
+                (
+                    "PICTURES".to_owned(),
+                    PathBuf::from("/home/user/My \"Pictures\"")
+                ),
+            ]
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn mountinfo_is_parsed() {
+        let contents = "\
+22 1 259:2 / / rw,relatime shared:1 - ext4 /dev/nvme0n1p2 rw
+23 22 0:21 / /proc rw,nosuid shared:5 - proc proc rw
+90 22 8:17 / /run/media/user/USB\\040Stick rw,nosuid shared:50 master:2 - vfat /dev/sdb1 rw
+";
+        assert_eq!(
+            parse_mountinfo(contents),
+            vec![
+                (PathBuf::from("/"), "ext4".to_owned()),
+                (PathBuf::from("/proc"), "proc".to_owned()),
+                (
+                    PathBuf::from("/run/media/user/USB Stick"),
+                    "vfat".to_owned()
+                ),
+            ]
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn mountinfo_escapes_are_undone() {
+        assert_eq!(unescape_mountinfo("a\\040b"), "a b");
+        assert_eq!(unescape_mountinfo("tab\\011nl\\012"), "tab\tnl\n");
+        assert_eq!(unescape_mountinfo("back\\134slash"), "back\\slash");
+        // Not an escape
+        assert_eq!(unescape_mountinfo("a\\08b\\"), "a\\08b\\");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn system_mounts_are_recognized() {
+        assert!(is_system_mount(Path::new("/boot/efi")));
+        assert!(is_system_mount(Path::new("/run/user/1000")));
+        assert!(!is_system_mount(Path::new("/run/media/user/USB")));
+        assert!(!is_system_mount(Path::new("/")));
+        assert!(!is_system_mount(Path::new("/mnt/data")));
+        // Only whole components count
+        assert!(!is_system_mount(Path::new("/varnish")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn file_uris_are_decoded() {
+        assert_eq!(
+            file_uri_to_path("file:///home/user/My%20Files"),
+            Some(PathBuf::from("/home/user/My Files"))
+        );
+        assert_eq!(
+            file_uri_to_path("file://localhost/tmp"),
+            Some(PathBuf::from("/tmp"))
+        );
+        assert_eq!(file_uri_to_path("sftp://host/home"), None);
+        assert_eq!(file_uri_to_path("file://host/home"), None);
+        assert_eq!(file_uri_to_path("file:///broken%2"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn bookmarks_are_parsed() {
+        assert_eq!(
+            parse_bookmark("file:///home/user/Work%20Stuff Work"),
+            Some(("Work".to_owned(), PathBuf::from("/home/user/Work Stuff")))
+        );
+        assert_eq!(
+            parse_bookmark("file:///home/user/Caf%C3%A9"),
+            Some(("Café".to_owned(), PathBuf::from("/home/user/Café")))
+        );
+        assert_eq!(parse_bookmark("sftp://server/srv Server"), None);
+    }
+}
@@ -0,0 +1,397 @@
+use std::{
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use dioxus::prelude::*;
+use tokio::sync::oneshot;
+
+use crate::{
+    ExplorerEntry, FileExplorer, FileSystem,
+    details::{format_modified, format_permissions, format_size, owner_name},
+    thumbnails,
+};
+
+/// How much of a text file is shown
+const TEXT_LIMIT: usize = 32 * 1024;
+/// How much of a binary file is dumped
+const HEX_LIMIT: usize = 4 * 1024;
+/// Larger images are not previewed
+const MAX_IMAGE_SIZE: usize = 64 * 1024 * 1024;
+/// The longest edge of an image preview
+const IMAGE_EDGE: u32 = 640;
+/// How long focus has to rest on an entry before it is loaded, so moving through entries does not load each
+const LOAD_DELAY: Duration = Duration::from_millis(150);
+
+/// What the preview shows of the contents of a file.
+enum Content {
+    Image(String),
+    Text {
+        lines: Vec<String>,
+        encoding: &'static str,
+        is_truncated: bool,
+    },
+    Binary {
+        dump: String,
+        is_truncated: bool,
+    },
+    /// Directories have no contents to show
+    None,
+    Failed(String),
+}
+
+/// What is only known on the local disk.
+#[derive(Default)]
+struct LocalDetails {
+    created: Option<SystemTime>,
+    accessed: Option<SystemTime>,
+    symlink_target: Option<PathBuf>,
+}
+
+/// The focused entry of `explorer`: its contents and metadata.
+#[component]
+pub(crate) fn PreviewPane(explorer: Signal<FileExplorer>) -> Element {
+    let reader = explorer.read();
+    let Some(entry) = reader.focused_entry().cloned() else {
+        return rsx! {
+            div { class: "fp-preview fp-preview-empty", "Click an entry to preview it" }
+        };
+    };
+    // The listing resolves symlinks, the path of the symlink itself is the one in the current directory
+    let link = reader.current().join(entry.name());
+    let file_system = reader.file_system().clone();
+    drop(reader);
+    rsx! {
+        div { class: "fp-preview",
+            Preview { entry, link, file_system }
+        }
+    }
+}
+
+#[component]
+fn Preview(entry: ExplorerEntry, link: PathBuf, file_system: FileSystem) -> Element {
+    let focused = entry.clone();
+    // Restarted when focus moves, which drops the receiver of the previous load and so cancels it
+    let preview = use_resource(use_reactive!(|(focused, link, file_system)| async move {
+        tokio::time::sleep(LOAD_DELAY).await;
+        let (sender, receiver) = oneshot::channel();
+        tokio::task::spawn_blocking(move || {
+            let content = load(&focused, &file_system, || sender.is_closed());
+            let details = if file_system.is_local() {
+                local_details(&focused, &link)
+            } else {
+                LocalDetails::default()
+            };
+            let _ = sender.send((content, details));
+        });
+        receiver.await.ok()
+    }));
+
+    let preview = preview.read();
+    let (content, details) = match preview.as_ref() {
+        Some(Some((content, details))) => (Some(content), Some(details)),
+        _ => (None, None),
+    };
+    let mut metadata = Vec::new();
+    if let Some(size) = entry.size() {
+        metadata.push(("Size", format!("{} ({size} bytes)", format_size(size))));
+    }
+    let times = [
+        ("Modified", entry.modified()),
+        ("Created", details.and_then(|details| details.created)),
+        ("Accessed", details.and_then(|details| details.accessed)),
+    ];
+    for (label, time) in times {
+        if let Some(time) = time {
+            metadata.push((label, format_modified(time)));
+        }
+    }
+    let kind = match entry.mime_type() {
+        _ if entry.is_dir() => "Folder",
+        Some(mime_type) => mime_type,
+        None => "Unknown",
+    };
+    metadata.push(("Type", kind.to_owned()));
+    if let Some(permissions) = entry.permissions() {
+        metadata.push((
+            "Permissions",
+            format_permissions(permissions, entry.is_dir()),
+        ));
+    }
+    if let Some(owner) = entry.owner() {
+        metadata.push(("Owner", owner_name(owner)));
+    }
+    if let Some(target) = details.and_then(|details| details.symlink_target.as_ref()) {
+        metadata.push(("Links to", target.display().to_string()));
+    }
+
+    rsx! {
+        h2 { class: "fp-preview-name", "{entry.name()}" }
+        match content {
+            None => rsx! {
+                div { class: "fp-loading",
+                    i { class: "material-icons fp-spin", "autorenew" }
+                    "Loading preview…"
+                }
+            },
+            Some(Content::Image(data_uri)) => rsx! {
+                img { class: "fp-preview-image", src: "{data_uri}" }
+            },
+            Some(Content::Text { lines, encoding, is_truncated }) => rsx! {
+                div { class: "fp-preview-note",
+                    "{encoding}"
+                    if *is_truncated {
+                        ", first {TEXT_LIMIT / 1024} KB"
+                    }
+                }
+                div { class: "fp-preview-text",
+                    for (index , line) in lines.iter().enumerate() {
+                        div { class: "fp-preview-line",
+                            span { class: "fp-line-number", "{index + 1}" }
+                            span { "{line}" }
+                        }
+                    }
+                }
+            },
+            Some(Content::Binary { dump, is_truncated }) => rsx! {
+                div { class: "fp-preview-note",
+                    "Binary"
+                    if *is_truncated {
+                        ", first {HEX_LIMIT / 1024} KB"
+                    }
+                }
+                pre { class: "fp-preview-hex", "{dump}" }
+            },
+            Some(Content::None) => rsx! {},
+            Some(Content::Failed(err)) => rsx! {
+                div { class: "fp-error", "{err}" }
+            },
+        }
+        table { class: "fp-preview-metadata",
+            for (label , value) in metadata {
+                tr {
+                    th { "{label}" }
+                    td { "{value}" }
+                }
+            }
+        }
+    }
+}
+
+/// Reads as much of the file as its preview needs. Stops early once `is_cancelled` returns true.
+fn load(
+    entry: &ExplorerEntry,
+    file_system: &FileSystem,
+    is_cancelled: impl Fn() -> bool,
+) -> Content {
+    if entry.is_dir() {
+        return Content::None;
+    }
+    let path = entry.path();
+    if thumbnails::is_supported(path) {
+        if entry
+            .size()
+            .is_some_and(|size| size > MAX_IMAGE_SIZE as u64)
+        {
+            return Content::Failed("The image is too large to preview".to_owned());
+        }
+        let data = match file_system.read_start(path, MAX_IMAGE_SIZE) {
+            Ok(data) => data,
+            Err(err) => return Content::Failed(err.to_string()),
+        };
+        if is_cancelled() {
+            return Content::None;
+        }
+        // Misnamed files are shown as what they contain
+        if let Ok(data_uri) = thumbnails::preview(&data, IMAGE_EDGE) {
+            return Content::Image(data_uri);
+        }
+    }
+    if is_cancelled() {
+        return Content::None;
+    }
+    let data = match file_system.read_start(path, TEXT_LIMIT) {
+        Ok(data) => data,
+        Err(err) => return Content::Failed(err.to_string()),
+    };
+    let is_truncated = |limit: usize| entry.size().is_some_and(|size| size > limit as u64);
+    match decode_text(&data, is_truncated(TEXT_LIMIT)) {
+        Some((text, encoding)) => Content::Text {
+            lines: text.lines().map(str::to_owned).collect(),
+            encoding,
+            is_truncated: is_truncated(TEXT_LIMIT),
+        },
+        None => Content::Binary {
+            dump: hex_dump(&data[..data.len().min(HEX_LIMIT)]),
+            is_truncated: is_truncated(HEX_LIMIT),
+        },
+    }
+}
+
+fn local_details(entry: &ExplorerEntry, link: &Path) -> LocalDetails {
+    let metadata = fs::metadata(entry.path()).ok();
+    let is_symlink = fs::symlink_metadata(link).is_ok_and(|metadata| metadata.is_symlink());
+    LocalDetails {
+        created: metadata
+            .as_ref()
+            .and_then(|metadata| metadata.created().ok()),
+        accessed: metadata.and_then(|metadata| metadata.accessed().ok()),
+        symlink_target: is_symlink.then(|| fs::read_link(link).ok()).flatten(),
+    }
+}
+
+/// Decodes `data` as text and guesses its encoding: UTF-16 and UTF-8 with a byte order mark, then UTF-8, then Latin-1
+/// if there are few control characters. None if it looks binary. `is_truncated` allows a character cut off at the end.
+fn decode_text(data: &[u8], is_truncated: bool) -> Option<(String, &'static str)> {
+    if let Some(rest) = data.strip_prefix(b"\xEF\xBB\xBF") {
+        return Some((String::from_utf8_lossy(rest).into_owned(), "UTF-8 with BOM"));
+    }
+    let utf16 = |rest: &[u8], from_bytes: fn([u8; 2]) -> u16| {
+        let units = rest
+            .chunks_exact(2)
+            .map(|pair| from_bytes([pair[0], pair[1]]));
+        char::decode_utf16(units)
+            .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+            .collect::<String>()
+    };
+    if let Some(rest) = data.strip_prefix(b"\xFF\xFE") {
+        return Some((utf16(rest, u16::from_le_bytes), "UTF-16 LE"));
+    }
+    if let Some(rest) = data.strip_prefix(b"\xFE\xFF") {
+        return Some((utf16(rest, u16::from_be_bytes), "UTF-16 BE"));
+    }
+    if data.contains(&0) {
+        return None;
+    }
+    let text = match std::str::from_utf8(data) {
+        Ok(text) => Some(text),
+        // Only the last character is incomplete
+        Err(err) if is_truncated && err.error_len().is_none() => {
+            Some(std::str::from_utf8(&data[..err.valid_up_to()]).expect("Valid up to here"))
+        }
+        Err(_) => None,
+    };
+    if let Some(text) = text {
+        let encoding = if text.is_ascii() { "ASCII" } else { "UTF-8" };
+        return Some((text.to_owned(), encoding));
+    }
+    let controls = data
+        .iter()
+        .filter(|byte| byte.is_ascii_control() && !b"\t\n\r\x0C\x1B".contains(byte))
+        .count();
+    (controls * 100 <= data.len()).then(|| {
+        let text = data.iter().map(|&byte| byte as char).collect();
+        (text, "ISO-8859-1")
+    })
+}
+
+/// Formats `data` like `hexdump -C`: the offset, 16 bytes in hex and them as ASCII.
+fn hex_dump(data: &[u8]) -> String {
+    let mut dump = String::with_capacity(data.len() * 5);
+    for (index, line) in data.chunks(16).enumerate() {
+        let _ = write!(dump, "{:08x} ", index * 16);
+        for column in 0..16 {
+            if column == 8 {
+                dump.push(' ');
+            }
+            match line.get(column) {
+                Some(byte) => _ = write!(dump, " {byte:02x}"),
+                None => dump.push_str("   "),
+            }
+        }
+        dump.push_str("  |");
+        dump.extend(line.iter().map(|&byte| {
+            if byte.is_ascii_graphic() || byte == b' ' {
+                byte as char
+            } else {
+                '.'
+            }
+        }));
+        dump.push_str("|\n");
+    }
+    dump
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_is_decoded_by_its_byte_order_mark() {
+        assert_eq!(
+            decode_text(b"\xEF\xBB\xBFh\xC3\xA9", false),
+            Some(("hé".to_owned(), "UTF-8 with BOM"))
+        );
+        assert_eq!(
+            decode_text(b"\xFF\xFEh\0i\0", false),
+            Some(("hi".to_owned(), "UTF-16 LE"))
+        );
+        assert_eq!(
+            decode_text(b"\xFE\xFF\0h\0i", false),
+            Some(("hi".to_owned(), "UTF-16 BE"))
+        );
+    }
+
+    #[test]
+    fn text_without_a_byte_order_mark_is_utf8_or_latin1() {
+        assert_eq!(
+            decode_text(b"plain", false),
+            Some(("plain".to_owned(), "ASCII"))
+        );
+        assert_eq!(
+            decode_text("caf\u{e9}".as_bytes(), false),
+            Some(("caf\u{e9}".to_owned(), "UTF-8"))
+        );
+        // Invalid as UTF-8, but a Latin-1 é
+        assert_eq!(
+            decode_text(b"caf\xE9 au lait", false),
+            Some(("caf\u{e9} au lait".to_owned(), "ISO-8859-1"))
+        );
+    }
+
+    #[test]
+    fn binary_data_is_not_text() {
+        assert_eq!(decode_text(b"ELF\0\x01", false), None);
+        assert_eq!(decode_text(b"\x01\x02\x03\x04invalid\xFF", false), None);
+    }
+
+    #[test]
+    fn a_character_cut_off_at_the_byte_limit_is_dropped() {
+        // The first two bytes of the three of €
+        let data = b"price: \xE2\x82";
+        assert_eq!(
+            decode_text(data, true),
+            Some(("price: ".to_owned(), "ASCII"))
+        );
+        // The whole file, so the bytes are really invalid
+        assert_eq!(
+            decode_text(data, false),
+            Some(("price: \u{e2}\u{82}".to_owned(), "ISO-8859-1"))
+        );
+        // Invalid before the end is not excused by truncation
+        assert_eq!(
+            decode_text(b"\xE2\x82 price", true),
+            Some(("\u{e2}\u{82} price".to_owned(), "ISO-8859-1"))
+        );
+    }
+
+    #[test]
+    fn hex_dump_pads_the_final_partial_line() {
+        assert_eq!(
+            hex_dump(b"0123456789abcdefghij"),
+            "00000000  30 31 32 33 34 35 36 37  38 39 61 62 63 64 65 66  |0123456789abcdef|\n\
+             00000010  67 68 69 6a                                       |ghij|\n"
+        );
+    }
+
+    #[test]
+    fn hex_dump_shows_unprintable_bytes_as_dots() {
+        assert_eq!(
+            hex_dump(b"a\0 \n\xFF"),
+            "00000000  61 00 20 0a ff                                    |a. ..|\n"
+        );
+        assert_eq!(hex_dump(b""), "");
+    }
+}
@@ -0,0 +1,320 @@
+use std::{
+    fmt, fs,
+    io::{self, Read},
+    ops::Deref,
+    path::{Path, PathBuf},
+    sync::{Arc, OnceLock},
+    time::SystemTime,
+};
+
+/// What the integrated picker browses. The local disk, [LocalFileSystem], is the default, but any tree addressed by
+/// paths works: an in-memory tree, an archive, assets bundled with the app or a backend server. Navigation, selection
+/// and the resulting [crate::VirtualPaths] behave the same for all of them.
+///
+/// Calls may block, the picker makes them off the ui thread where it can.
+pub trait FileSystemProvider: Send + Sync {
+    /// Lists the entries of the directory at `path`. Symlinks are resolved: a symlink is listed with the path and
+    /// metadata of what it points to, but keeps its own name.
+    fn read_dir(
+        &self,
+        path: &Path,
+    ) -> io::Result<Box<dyn Iterator<Item = io::Result<DirEntry>> + '_>>;
+
+    /// The metadata of the entry at `path`, following symlinks.
+    fn metadata(&self, path: &Path) -> io::Result<EntryMetadata>;
+
+    /// The parent directory of `path`. None at the top of the tree.
+    fn parent(&self, path: &Path) -> Option<PathBuf> {
+        path.parent().map(Path::to_path_buf)
+    }
+
+    /// Makes `path`, e.g. an `open_at` given by the app, absolute. Returns it as is by default. Pickers with roots
+    /// compare the result against them, so it should resolve symlinks where the file system has them.
+    fn absolute(&self, path: &Path) -> io::Result<PathBuf> {
+        Ok(path.to_path_buf())
+    }
+
+    /// Reads the whole file at `path`.
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+
+    /// Reads at most `limit` bytes from the start of the file at `path`, e.g. for a preview. Reads the whole file and
+    /// truncates it by default.
+    fn read_start(&self, path: &Path, limit: usize) -> io::Result<Vec<u8>> {
+        let mut data = self.read(path)?;
+        data.truncate(limit);
+        Ok(data)
+    }
+
+    /// Writes `data` to the file at `path`, replacing it if it exists. Unsupported by default, for read-only trees.
+    fn write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        let _ = (path, data);
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "The file system is read-only",
+        ))
+    }
+
+    /// Watches the directory at `path`, not recursively, calling `on_change` with every change to its entries until
+    /// the returned guard is dropped. Unsupported by default, then the picker only picks up changes on reload.
+    fn watch(&self, path: &Path, on_change: OnChange) -> io::Result<Box<dyn Send>> {
+        let _ = (path, on_change);
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "The file system can not be watched",
+        ))
+    }
+
+    /// Returns true if the paths are paths on the local disk. Only then native dialogs can be used and results are
+    /// read straight from disk.
+    fn is_local(&self) -> bool {
+        false
+    }
+}
+
+/// If an entry is a file or a directory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileSystemType {
+    File,
+    Directory,
+}
+
+/// Called by a [FileSystemProvider::watch], possibly from another thread.
+pub type OnChange = Box<dyn Fn(WatchEvent) + Send + Sync>;
+
+/// A change in a watched directory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WatchEvent {
+    Created(PathBuf),
+    Removed(PathBuf),
+    Renamed {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    /// The contents or metadata of the entry changed
+    Modified(PathBuf),
+    /// Changes were missed, the directory has to be listed again
+    Rescan,
+}
+
+/// An entry listed by [FileSystemProvider::read_dir].
+#[derive(Clone, Debug, PartialEq)]
+pub struct DirEntry {
+    /// The resolved path
+    pub path: PathBuf,
+    /// Usually the file name of [DirEntry::path], except when the entry is a symlink
+    pub name: String,
+    pub metadata: EntryMetadata,
+}
+
+/// What a [FileSystemProvider] knows about an entry.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EntryMetadata {
+    pub kind: FileSystemType,
+    /// The size in bytes. None for directories.
+    pub size: Option<u64>,
+    /// When the entry was last modified, if known.
+    pub modified: Option<SystemTime>,
+    /// The Unix permission bits, e.g. `0o644`. None where there are none.
+    pub permissions: Option<u32>,
+    /// The user id of the owner. None where there is none.
+    pub owner: Option<u32>,
+}
+
+/// The local disk, through [std::fs].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LocalFileSystem;
+
+impl LocalFileSystem {
+    fn entry(entry: io::Result<fs::DirEntry>) -> io::Result<DirEntry> {
+        let entry = entry?;
+        let mut path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let mut metadata = entry.metadata()?;
+        if metadata.is_symlink() {
+            path = fs::canonicalize(path)?;
+            metadata = fs::metadata(&path)?;
+            debug_assert!(
+                !metadata.is_symlink(),
+                "canonicalize should resolve symlinks"
+            );
+        }
+        Ok(DirEntry {
+            path,
+            name,
+            metadata: metadata.into(),
+        })
+    }
+}
+
+impl FileSystemProvider for LocalFileSystem {
+    fn read_dir(
+        &self,
+        path: &Path,
+    ) -> io::Result<Box<dyn Iterator<Item = io::Result<DirEntry>> + '_>> {
+        Ok(Box::new(fs::read_dir(path)?.map(Self::entry)))
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<EntryMetadata> {
+        fs::metadata(path).map(Into::into)
+    }
+
+    fn absolute(&self, path: &Path) -> io::Result<PathBuf> {
+        fs::canonicalize(path).or_else(|_| std::path::absolute(path))
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        fs::read(path)
+    }
+
+    fn read_start(&self, path: &Path, limit: usize) -> io::Result<Vec<u8>> {
+        let mut data = Vec::new();
+        fs::File::open(path)?
+            .take(limit as u64)
+            .read_to_end(&mut data)?;
+        Ok(data)
+    }
+
+    fn write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        fs::write(path, data)
+    }
+
+    #[cfg(any(
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    fn watch(&self, path: &Path, on_change: OnChange) -> io::Result<Box<dyn Send>> {
+        use notify::{
+            EventKind, RecursiveMode, Watcher,
+            event::{ModifyKind, RenameMode},
+        };
+
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(_) => return on_change(WatchEvent::Rescan),
+                };
+                if event.need_rescan() {
+                    return on_change(WatchEvent::Rescan);
+                }
+                let mut paths = event.paths.into_iter();
+                let Some(path) = paths.next() else {
+                    return;
+                };
+                let change = match event.kind {
+                    EventKind::Create(_) => WatchEvent::Created(path),
+                    EventKind::Remove(_) => WatchEvent::Removed(path),
+                    EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => match paths.next() {
+                        Some(to) => WatchEvent::Renamed { from: path, to },
+                        None => WatchEvent::Rescan,
+                    },
+                    EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+                        WatchEvent::Removed(path)
+                    }
+                    EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+                        WatchEvent::Created(path)
+                    }
+                    // Only one side of the rename is known
+                    EventKind::Modify(ModifyKind::Name(_)) if path.exists() => {
+                        WatchEvent::Created(path)
+                    }
+                    EventKind::Modify(ModifyKind::Name(_)) => WatchEvent::Removed(path),
+                    EventKind::Modify(_) => WatchEvent::Modified(path),
+                    EventKind::Access(_) => return,
+                    EventKind::Any | EventKind::Other => WatchEvent::Rescan,
+                };
+                on_change(change);
+            })
+            .map_err(io::Error::other)?;
+        watcher
+            .watch(path, RecursiveMode::NonRecursive)
+            .map_err(io::Error::other)?;
+        Ok(Box::new(watcher))
+    }
+
+    fn is_local(&self) -> bool {
+        true
+    }
+}
+
+impl From<fs::Metadata> for EntryMetadata {
+    fn from(metadata: fs::Metadata) -> Self {
+        #[cfg(unix)]
+        let (permissions, owner) = {
+            use std::os::unix::fs::MetadataExt;
+            (Some(metadata.mode() & 0o7777), Some(metadata.uid()))
+        };
+        #[cfg(not(unix))]
+        let (permissions, owner) = (None, None);
+        Self {
+            kind: if metadata.is_dir() {
+                FileSystemType::Directory
+            } else {
+                FileSystemType::File
+            },
+            size: metadata.is_file().then_some(metadata.len()),
+            modified: metadata.modified().ok(),
+            permissions,
+            owner,
+        }
+    }
+}
+
+/// A shared [FileSystemProvider], as passed to the pickers. Defaults to [LocalFileSystem]. Two handles are equal if
+/// they share the same provider.
+#[derive(Clone)]
+pub struct FileSystem(Arc<dyn FileSystemProvider>);
+
+impl FileSystem {
+    /// Shares `provider` with every picker the handle is passed to.
+    pub fn new(provider: impl FileSystemProvider + 'static) -> Self {
+        Self(Arc::new(provider))
+    }
+}
+
+impl Default for FileSystem {
+    fn default() -> Self {
+        // Shared, so default handles are equal and do not re-render components
+        static LOCAL: OnceLock<Arc<dyn FileSystemProvider>> = OnceLock::new();
+        Self(LOCAL.get_or_init(|| Arc::new(LocalFileSystem)).clone())
+    }
+}
+
+impl<P: FileSystemProvider + 'static> From<P> for FileSystem {
+    fn from(provider: P) -> Self {
+        Self::new(provider)
+    }
+}
+
+impl From<Arc<dyn FileSystemProvider>> for FileSystem {
+    fn from(provider: Arc<dyn FileSystemProvider>) -> Self {
+        Self(provider)
+    }
+}
+
+impl Deref for FileSystem {
+    type Target = dyn FileSystemProvider;
+
+    fn deref(&self) -> &Self::Target {
+        &*self.0
+    }
+}
+
+impl PartialEq for FileSystem {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl fmt::Debug for FileSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileSystem")
+            .field("is_local", &self.is_local())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,374 @@
+use std::{mem, ops::Range, sync::Arc};
+
+use dioxus::prelude::*;
+use fuzzy_matcher::{FuzzyMatcher, skim::SkimMatcherV2};
+use tokio::sync::oneshot;
+
+use crate::{
+    FileExplorer, FileSystemType,
+    filter::Highlighted,
+    walk::{WalkEntry, walk},
+};
+
+/// How deep below the current directory is indexed
+const MAX_DEPTH: usize = 12;
+/// Indexing stops here, e.g. when started at `/`
+const MAX_INDEXED: usize = 200_000;
+const MAX_RESULTS: usize = 50;
+const BATCH_SIZE: usize = 512;
+/// How many entries are ranked between checks for a newer query
+const CANCEL_CHECK_INTERVAL: usize = 4096;
+
+/// A ranked entry, with the ranges of its relative path that matched.
+#[derive(Clone, PartialEq)]
+struct Ranked {
+    entry: WalkEntry,
+    highlights: Vec<Range<usize>>,
+}
+
+/// Finds an entry anywhere below the current directory by typing parts of its path, e.g. `cfgprod` for
+/// `config/production.toml`. The tree is indexed in the background when opened, and the index dropped when closed.
+#[component]
+pub(crate) fn QuickOpen(explorer: Signal<FileExplorer>, on_close: Callback) -> Element {
+    // What is ranked, replaced as indexing goes on. Shared, so ranking on another thread does not copy it
+    let mut index: Signal<Arc<Vec<WalkEntry>>> = use_signal(Arc::default);
+    let mut indexed = use_signal(|| 0);
+    let mut collected = use_hook(|| CopyValue::new(Vec::<WalkEntry>::new()));
+    let mut query = use_signal(String::new);
+    let mut active = use_signal(|| 0);
+    let mut error: Signal<Option<String>> = use_signal(|| None);
+    let root = use_memo(move || {
+        let reader = explorer.read();
+        (!reader.is_listing_roots()).then(|| reader.current().to_path_buf())
+    });
+    // Cancelling drops the receiver, which stops the walk at its next batch
+    let mut indexing = use_resource(move || async move {
+        collected.write().clear();
+        index.set(Arc::default());
+        indexed.set(0);
+        let Some(root) = root() else {
+            return;
+        };
+        let (file_system, roots) = {
+            let reader = explorer.peek();
+            (reader.file_system().clone(), reader.roots().to_vec())
+        };
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(4);
+        tokio::task::spawn_blocking(move || {
+            let mut batch = Vec::with_capacity(BATCH_SIZE);
+            let mut indexed = 0;
+            walk(&file_system, &root, MAX_DEPTH, &roots, |entry| {
+                batch.push(entry);
+                indexed += 1;
+                if batch.len() >= BATCH_SIZE && sender.blocking_send(mem::take(&mut batch)).is_err()
+                {
+                    return false;
+                }
+                indexed < MAX_INDEXED
+            });
+            let _ = sender.blocking_send(batch);
+        });
+        while let Some(batch) = receiver.recv().await {
+            collected.write().extend(batch);
+            let count = collected.read().len();
+            indexed.set(count);
+            // Every new index is ranked again in full, so it is only replaced once it grew by a quarter
+            if count >= index.peek().len() * 5 / 4 {
+                index.set(Arc::new(collected.read().clone()));
+            }
+        }
+        if collected.read().len() != index.peek().len() {
+            index.set(Arc::new(collected.read().clone()));
+        }
+    });
+    // A new query or index drops the ranking in flight, which stops its thread
+    let results = use_resource(move || async move {
+        let query = query();
+        let index = index();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let (sender, receiver) = oneshot::channel();
+        tokio::task::spawn_blocking(move || {
+            let ranked = rank(&index, &query, || sender.is_closed());
+            let _ = sender.send(ranked);
+        });
+        receiver.await.unwrap_or_default()
+    });
+    let results = move || results.read().clone().unwrap_or_default();
+
+    let mut open = move |result: Option<Ranked>, select: bool| {
+        let Some(Ranked { entry, .. }) = result else {
+            return;
+        };
+        match open_entry(&mut explorer.write(), &entry, select) {
+            Ok(()) => on_close.call(()),
+            Err(message) => error.set(Some(message)),
+        }
+    };
+
+    let indexed = indexed();
+    let status = match *indexing.state().read() {
+        UseResourceState::Pending => format!("Indexing… {indexed} entries"),
+        UseResourceState::Stopped => format!("Stopped after {indexed} entries"),
+        _ if indexed >= MAX_INDEXED => format!("The first {indexed} entries indexed"),
+        _ => format!("{indexed} entries indexed"),
+    };
+    let is_indexing = *indexing.state().read() == UseResourceState::Pending;
+    rsx! {
+        div { class: "fp-quick-open",
+            div { class: "fp-row",
+                i { class: "material-icons", "search" }
+                input {
+                    class: "fp-input",
+                    r#type: "text",
+                    spellcheck: false,
+                    placeholder: "Type parts of a path below this directory",
+                    value: "{query}",
+                    onmounted: move |event| async move {
+                        let _ = event.set_focus(true).await;
+                    },
+                    oninput: move |event| {
+                        query.set(event.value());
+                        active.set(0);
+                        error.set(None);
+                    },
+                    onkeydown: move |event| {
+                        let count = results().len();
+                        match event.key() {
+                            Key::ArrowDown if count > 0 => {
+                                event.prevent_default();
+                                active.set((active() + 1) % count);
+                            }
+                            Key::ArrowUp if count > 0 => {
+                                event.prevent_default();
+                                active.set((active() + count - 1) % count);
+                            }
+                            // With Ctrl or Cmd, only revealed
+                            Key::Enter => {
+                                let modifiers = event.modifiers();
+                                let select = !(modifiers.ctrl() || modifiers.meta());
+                                open(results().get(active()).cloned(), select);
+                            }
+                            Key::Escape => on_close.call(()),
+                            _ => {}
+                        }
+                    },
+                }
+                i {
+                    class: "material-icons",
+                    title: "Close",
+                    onclick: move |_| on_close.call(()),
+                    "close"
+                }
+            }
+            div { class: "fp-row fp-quick-open-status",
+                span { "{status}" }
+                if is_indexing {
+                    button {
+                        onclick: move |_| {
+                            indexing.cancel();
+                            index.set(Arc::new(collected.read().clone()));
+                        },
+                        "Stop"
+                    }
+                }
+            }
+            if let Some(error) = error() {
+                div { class: "fp-error", "{error}" }
+            }
+            div { class: "fp-quick-open-results",
+                for (index , result) in results().into_iter().enumerate() {
+                    div {
+                        key: "{result.entry.relative}",
+                        class: "fp-row fp-quick-open-result",
+                        class: if index == active() { "fp-active" },
+                        onclick: {
+                            let result = result.clone();
+                            move |_| open(Some(result.clone()), true)
+                        },
+                        i { class: "material-icons",
+                            if result.entry.kind == FileSystemType::Directory {
+                                "folder"
+                            } else {
+                                "description"
+                            }
+                        }
+                        span { class: "flex-grow",
+                            Highlighted {
+                                text: result.entry.relative.clone(),
+                                ranges: result.highlights.clone(),
+                            }
+                        }
+                        i {
+                            class: "material-icons",
+                            title: "Reveal without selecting",
+                            onclick: move |event| {
+                                event.stop_propagation();
+                                open(Some(result.clone()), false);
+                            },
+                            "my_location"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The best matches of `query` among the relative paths in `index`, best first. Ties go to the shorter path. Gives up,
+/// with no results, once `is_cancelled` returns true.
+fn rank(index: &[WalkEntry], query: &str, is_cancelled: impl Fn() -> bool) -> Vec<Ranked> {
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let matcher = SkimMatcherV2::default();
+    let mut scored: Vec<(i64, &WalkEntry)> = Vec::new();
+    for (position, entry) in index.iter().enumerate() {
+        if position % CANCEL_CHECK_INTERVAL == 0 && is_cancelled() {
+            return Vec::new();
+        }
+        if let Some(score) = matcher.fuzzy_match(&entry.relative, query) {
+            scored.push((score, entry));
+        }
+    }
+    scored.sort_by(|(a_score, a), (b_score, b)| {
+        b_score
+            .cmp(a_score)
+            .then_with(|| a.relative.len().cmp(&b.relative.len()))
+    });
+    scored
+        .into_iter()
+        .take(MAX_RESULTS)
+        .map(|(_, entry)| {
+            let indices = matcher
+                .fuzzy_indices(&entry.relative, query)
+                .map(|(_, indices)| indices)
+                .unwrap_or_default();
+            Ranked {
+                highlights: char_ranges(&entry.relative, &indices),
+                entry: entry.clone(),
+            }
+        })
+        .collect()
+}
+
+/// Turns the sorted character indices of `text` into byte ranges, joining adjacent ones.
+fn char_ranges(text: &str, indices: &[usize]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let mut indices = indices.iter().peekable();
+    for (char_index, (start, c)) in text.char_indices().enumerate() {
+        if indices.next_if(|&&index| index == char_index).is_none() {
+            continue;
+        }
+        let end = start + c.len_utf8();
+        match ranges.last_mut() {
+            Some(last) if last.end == start => last.end = end,
+            _ => ranges.push(start..end),
+        }
+    }
+    ranges
+}
+
+/// Opens a directory, or reveals a file and, if `select`, selects it.
+fn open_entry(explorer: &mut FileExplorer, entry: &WalkEntry, select: bool) -> Result<(), String> {
+    // The walk reports symlinks by the path they were found at, listings by what they point to
+    let file_system = explorer.file_system().clone();
+    let path = file_system
+        .absolute(&entry.path)
+        .unwrap_or_else(|_| entry.path.clone());
+    if !explorer.is_allowed(&path) {
+        return Err(format!(
+            "{} is outside the allowed directories",
+            path.display()
+        ));
+    }
+    if entry.kind == FileSystemType::Directory {
+        explorer.navigate(path);
+        return Ok(());
+    }
+    let revealed = explorer
+        .reveal(&path)
+        .ok_or_else(|| format!("{} could not be opened", path.display()))?;
+    explorer.focused = Some(revealed.path().to_path_buf());
+    if select {
+        if !explorer.select(&revealed) {
+            return Err(format!("{} can not be selected", revealed.name()));
+        }
+        explorer.is_selecting = true;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+// Ranges are what is highlighted, a single one is meant
+#[allow(clippy::single_range_in_vec_init)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn index(paths: &[&str]) -> Vec<WalkEntry> {
+        paths
+            .iter()
+            .map(|relative| WalkEntry {
+                path: PathBuf::from("/root").join(relative),
+                relative: (*relative).to_owned(),
+                kind: FileSystemType::File,
+            })
+            .collect()
+    }
+
+    fn ranked(index: &[WalkEntry], query: &str) -> Vec<String> {
+        rank(index, query, || false)
+            .into_iter()
+            .map(|ranked| ranked.entry.relative)
+            .collect()
+    }
+
+    #[test]
+    fn char_ranges_join_adjacent_characters() {
+        assert_eq!(char_ranges("config", &[0, 1, 2, 4]), vec![0..3, 4..5]);
+        assert_eq!(char_ranges("config", &[]), Vec::<Range<usize>>::new());
+    }
+
+    #[test]
+    fn char_ranges_are_byte_ranges() {
+        assert_eq!(char_ranges("äbc", &[0, 1]), vec![0..3]);
+        assert_eq!(char_ranges("aéz", &[1, 2]), vec![1..4]);
+    }
+
+    #[test]
+    fn rank_keeps_only_matches() {
+        let index = index(&["config/production.toml", "src/main.rs", "readme.md"]);
+        assert_eq!(ranked(&index, "cfgprod"), ["config/production.toml"]);
+        assert!(ranked(&index, "xyz").is_empty());
+        assert!(ranked(&index, "").is_empty());
+    }
+
+    #[test]
+    fn rank_breaks_ties_by_length() {
+        let index = index(&["a/b/main.rs", "main.rs"]);
+        assert_eq!(ranked(&index, "main.rs"), ["main.rs", "a/b/main.rs"]);
+    }
+
+    #[test]
+    fn rank_highlights_what_matched() {
+        let index = index(&["src/main.rs"]);
+        let results = rank(&index, "main", || false);
+        assert_eq!(results[0].highlights, vec![4..8]);
+    }
+
+    #[test]
+    fn rank_stops_when_cancelled() {
+        let index = index(&["main.rs"]);
+        assert!(rank(&index, "main", || true).is_empty());
+    }
+
+    #[test]
+    fn rank_keeps_the_best_results() {
+        let paths: Vec<String> = (0..MAX_RESULTS * 2).map(|n| format!("file{n}")).collect();
+        let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+        assert_eq!(ranked(&index(&paths), "file").len(), MAX_RESULTS);
+    }
+}
@@ -0,0 +1,157 @@
+#[cfg(not(target_arch = "wasm32"))]
+pub use native::SaveTarget;
+#[cfg(target_arch = "wasm32")]
+pub use web::SaveTarget;
+
+/// Appends `.{extension}` to `name` if it does not already end with it.
+pub(crate) fn enforce_extension(name: &str, extension: Option<&str>) -> String {
+    let Some(extension) = extension.map(|e| e.trim_start_matches('.')) else {
+        return name.to_owned();
+    };
+    let has_extension = name
+        .rsplit_once('.')
+        .is_some_and(|(stem, e)| !stem.is_empty() && e.eq_ignore_ascii_case(extension));
+    if has_extension || extension.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{name}.{extension}")
+    }
+}
+
+// Web-specific implementation module
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use std::io;
+
+    use wasm_bindgen::{JsCast, closure::Closure};
+    use web_sys::{Blob, BlobPropertyBag, HtmlAnchorElement, Url};
+
+    /// How long the blob url outlives the click. Firefox and Safari start the download asynchronously and abort it if
+    /// the url is revoked right away.
+    const REVOKE_DELAY_MS: i32 = 60_000;
+
+    /// Web implementation of a save target for WebAssembly targets. Writing to it downloads the data through the
+    /// browser.
+    pub struct SaveTarget {
+        file_name: String,
+    }
+
+    impl SaveTarget {
+        pub(crate) fn web(file_name: String) -> Self {
+            Self { file_name }
+        }
+
+        /// Returns the path as a `String`. On web the path will be the file name of the download.
+        pub fn path(&self) -> String {
+            self.file_name.clone()
+        }
+
+        /// Writes `data` to the target by starting a browser download.
+        pub async fn write(&self, data: impl AsRef<[u8]>) -> io::Result<()> {
+            fn js_error(error: wasm_bindgen::JsValue) -> io::Error {
+                io::Error::other(format!("{error:?}"))
+            }
+
+            let bytes = js_sys::Uint8Array::from(data.as_ref());
+            let parts = js_sys::Array::of1(&bytes);
+            let options = BlobPropertyBag::new();
+            options.set_type("application/octet-stream");
+            let blob =
+                Blob::new_with_u8_array_sequence_and_options(&parts, &options).map_err(js_error)?;
+            let url = Url::create_object_url_with_blob(&blob).map_err(js_error)?;
+
+            let window = web_sys::window()
+                .ok_or_else(|| io::Error::other("No window to start the download from"))?;
+            let document = window
+                .document()
+                .ok_or_else(|| io::Error::other("No document to start the download from"))?;
+            let body = document
+                .body()
+                .ok_or_else(|| io::Error::other("No body to start the download from"))?;
+            let anchor: HtmlAnchorElement = document
+                .create_element("a")
+                .map_err(js_error)?
+                .dyn_into()
+                .map_err(|element| js_error(element.into()))?;
+            anchor.set_href(&url);
+            anchor.set_download(&self.file_name);
+            // Firefox only follows clicks on anchors in the document
+            body.append_child(&anchor).map_err(js_error)?;
+            anchor.click();
+
+            let cleanup = Closure::once_into_js(move || {
+                let _ = Url::revoke_object_url(&url);
+                anchor.remove();
+            });
+            window
+                .set_timeout_with_callback_and_timeout_and_arguments_0(
+                    cleanup.unchecked_ref(),
+                    REVOKE_DELAY_MS,
+                )
+                .map_err(js_error)?;
+            Ok(())
+        }
+    }
+}
+
+// Native-specific implementation module
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use std::{io, path::PathBuf};
+    use tokio::fs;
+
+    /// Native implementation of a save target for non-WebAssembly targets
+    pub struct SaveTarget {
+        path: PathBuf,
+    }
+
+    impl SaveTarget {
+        pub(crate) fn native(path: PathBuf) -> Self {
+            Self { path }
+        }
+
+        /// Returns the path as a `String`.
+        pub fn path(&self) -> String {
+            self.path.to_string_lossy().into_owned()
+        }
+
+        /// Writes `data` to the target, replacing the file if it exists.
+        pub async fn write(&self, data: impl AsRef<[u8]>) -> io::Result<()> {
+            fs::write(&self.path, data).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn appends_a_missing_extension() {
+        assert_eq!(enforce_extension("report", Some("csv")), "report.csv");
+        assert_eq!(
+            enforce_extension("report.txt", Some("csv")),
+            "report.txt.csv"
+        );
+        assert_eq!(enforce_extension("report.", Some("csv")), "report..csv");
+    }
+
+    #[test]
+    fn keeps_a_present_extension_in_any_case() {
+        assert_eq!(enforce_extension("report.csv", Some("csv")), "report.csv");
+        assert_eq!(enforce_extension("report.CSV", Some("csv")), "report.CSV");
+        assert_eq!(enforce_extension("report.csv", Some(".csv")), "report.csv");
+    }
+
+    #[test]
+    fn a_dotfile_has_no_extension() {
+        assert_eq!(enforce_extension(".csv", Some("csv")), ".csv.csv");
+    }
+
+    #[test]
+    fn without_an_extension_the_name_is_kept() {
+        assert_eq!(enforce_extension("report", None), "report");
+        assert_eq!(enforce_extension("report", Some("")), "report");
+        assert_eq!(enforce_extension("report", Some(".")), "report");
+    }
+}
@@ -0,0 +1,85 @@
+use std::path::PathBuf;
+
+use dioxus::{logger::tracing::error, prelude::*};
+
+use crate::{
+    OverlaySize, PickError, PickOutcome, PickerWindow, SaveOptions, SaveTarget, use_file_picker,
+};
+
+/// Launches a "Save as…" dialog. The user chooses where a file should be written to and the chosen [SaveTarget] is
+/// passed to `on_submit`, the app then writes its data to it.
+#[component]
+pub fn FileSaverLauncher(
+    /// If true, on desktop will launch a native save dialog.
+    desktop_native: bool,
+    /// If true, on desktop will launch in a new window. This is treated as true if `desktop_native` is true.
+    desktop_windowed: bool,
+    /// The file name suggested to the user. On web this is the name of the download.
+    default_name: Option<String>,
+    /// The extension, e.g. `csv`, the file name is forced to end with.
+    extension: Option<String>,
+    /// If true, the user has to confirm choosing a file that already exists. Native dialogs always confirm. Has no
+    /// effect on web.
+    #[props(default = true)]
+    confirm_overwrite: bool,
+    /// The callback to call when the user has chosen where to save.
+    on_submit: Callback<SaveTarget, ()>,
+    /// The callback to call when the user backs out without choosing.
+    on_cancel: Option<Callback<(), ()>>,
+    /// The callback to call when the save dialog could not be shown or failed while open.
+    on_error: Option<Callback<PickError, ()>>,
+    /// The path to open the save dialog at. If null, defaults to current directory. Has no effect on web.
+    open_at: Option<PathBuf>,
+    /// The window of the integrated save dialog, if it opens in a new window.
+    #[props(default)]
+    window: PickerWindow,
+    /// How much of the window the integrated save dialog covers, if it opens in an overlay.
+    #[props(default)]
+    overlay_size: OverlaySize,
+    children: Element,
+) -> Element {
+    let picker = use_file_picker();
+    let options = SaveOptions {
+        desktop_native,
+        desktop_windowed,
+        default_name,
+        extension,
+        confirm_overwrite,
+        open_at,
+        window,
+        overlay_size,
+    };
+    let on_click = move |_event| {
+        let options = options.clone();
+        async move {
+            match picker.save(options).await {
+                PickOutcome::Selected(target) => on_submit.call(target),
+                PickOutcome::Cancelled => {
+                    if let Some(on_cancel) = on_cancel {
+                        on_cancel.call(());
+                    }
+                }
+                PickOutcome::Failed(error) => match on_error {
+                    Some(on_error) => on_error.call(error),
+                    None => error!("{error}"),
+                },
+            }
+        }
+    };
+    // Web
+    #[cfg(target_arch = "wasm32")]
+    {
+        rsx! {
+            button { onclick: on_click, {children} }
+            {picker.render()}
+        }
+    }
+    // Desktop/mobile
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        rsx! {
+            div { onclick: on_click, {children} }
+            {picker.render()}
+        }
+    }
+}
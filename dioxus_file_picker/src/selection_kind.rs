@@ -0,0 +1,30 @@
+/// What kind of file system entities a picker lets the user select.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SelectionKind {
+    /// Only files can be selected. Directories can still be navigated.
+    #[default]
+    Files,
+    /// Only directories can be selected.
+    Directories,
+    /// Both files and directories can be selected. Native dialogs and browsers cannot pick both at once, so on
+    /// desktop this always uses the integrated file picker and on web this behaves like [SelectionKind::Files].
+    FilesAndDirectories,
+}
+
+impl SelectionKind {
+    /// Returns true if files can be selected.
+    pub fn allows_files(self) -> bool {
+        matches!(
+            self,
+            SelectionKind::Files | SelectionKind::FilesAndDirectories
+        )
+    }
+
+    /// Returns true if directories can be selected.
+    pub fn allows_directories(self) -> bool {
+        matches!(
+            self,
+            SelectionKind::Directories | SelectionKind::FilesAndDirectories
+        )
+    }
+}
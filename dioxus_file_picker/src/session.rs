@@ -0,0 +1,259 @@
+use std::path::{Path, PathBuf};
+
+use dioxus::logger::tracing::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::SortOptions;
+
+/// Where a picker with a persistence key was left, restored the next time it opens. Stored per app, as JSON in the
+/// XDG state dir (`$XDG_STATE_HOME/<app>/file_picker.json`, or the platform's local data dir where there is none) on
+/// native and in localStorage on web.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct PickerSession {
+    /// The navigation history, the last entry is the most recent
+    pub(crate) history: Vec<PathBuf>,
+    /// How many entries back from the end of [history] the picker was
+    pub(crate) history_position: usize,
+    pub(crate) view: ViewSettings,
+}
+
+/// How the listing was shown.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub(crate) struct ViewSettings {
+    /// If the picker was in select mode
+    pub(crate) is_selecting: bool,
+    pub(crate) sort: SortOptions,
+    pub(crate) layout: Layout,
+}
+
+/// How the integrated file picker lays out entries.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum Layout {
+    /// Large icons
+    #[default]
+    Grid,
+    /// A table with the details of each entry
+    List,
+}
+
+/// How many directories of the navigation history are stored, the state file would otherwise grow with every visit.
+const MAX_HISTORY: usize = 100;
+
+impl PickerSession {
+    /// The directory the picker was left in.
+    pub(crate) fn last_dir(&self) -> Option<&Path> {
+        let index = self.history.len().checked_sub(self.history_position + 1)?;
+        Some(&self.history[index])
+    }
+
+    /// Records that `dir` was visited, dropping any forward history like navigating there would.
+    pub(crate) fn visit(&mut self, dir: PathBuf) {
+        self.history
+            .truncate(self.history.len().saturating_sub(self.history_position));
+        self.history_position = 0;
+        if self.history.last() != Some(&dir) {
+            self.history.push(dir);
+        }
+    }
+
+    /// Drops the oldest directories beyond [MAX_HISTORY], but never the current one.
+    fn limit_history(&mut self) {
+        let Some(current) = self.history.len().checked_sub(self.history_position + 1) else {
+            return;
+        };
+        let start = self.history.len().saturating_sub(MAX_HISTORY).min(current);
+        let end = (start + MAX_HISTORY).min(self.history.len());
+        self.history.truncate(end);
+        self.history.drain(..start);
+        self.history_position = end - 1 - current;
+    }
+}
+
+// Native
+//************************************************************************//
+
+#[cfg(not(target_arch = "wasm32"))]
+type Sessions = std::collections::BTreeMap<String, PickerSession>;
+
+/// The state file shared by all pickers of this app.
+#[cfg(not(target_arch = "wasm32"))]
+fn state_file() -> Option<PathBuf> {
+    let state_dir = dirs::state_dir().or_else(dirs::data_local_dir)?;
+    let app = std::env::current_exe()
+        .ok()?
+        .file_stem()?
+        .to_string_lossy()
+        .into_owned();
+    Some(state_dir.join(app).join("file_picker.json"))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_sessions(file: &Path) -> Sessions {
+    match std::fs::read(file) {
+        Ok(contents) => serde_json::from_slice(&contents).unwrap_or_else(|err| {
+            warn!(
+                "Ignoring the corrupt file picker state in {}: {err}",
+                file.display()
+            );
+            Sessions::new()
+        }),
+        Err(_) => Sessions::new(),
+    }
+}
+
+/// The session stored for `key`, if any.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn load(key: &str) -> Option<PickerSession> {
+    read_sessions(&state_file()?).remove(key)
+}
+
+/// Stores `session` for `key`. Failures are logged, a picker works the same without its session. Blocks on file io,
+/// so it is meant to run on a blocking thread.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn store(key: &str, mut session: PickerSession) {
+    // Pickers of the same app share the file, so one store must not overwrite what another just wrote
+    static STORING: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    let _storing = STORING
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let Some(file) = state_file() else {
+        warn!("No state directory to store the file picker session in");
+        return;
+    };
+    session.limit_history();
+    let mut sessions = read_sessions(&file);
+    if sessions.get(key) == Some(&session) {
+        return;
+    }
+    sessions.insert(key.to_owned(), session);
+    let result = (|| {
+        if let Some(dir) = file.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        // Written next to the state file first, so a crash never leaves it half written
+        let temp = file.with_extension("json.tmp");
+        std::fs::write(&temp, serde_json::to_vec_pretty(&sessions)?)?;
+        std::fs::rename(&temp, &file)
+    })();
+    if let Err(err) = result {
+        warn!(
+            "Could not store the file picker session in {}: {err}",
+            file.display()
+        );
+    }
+}
+
+// Web
+//************************************************************************//
+
+#[cfg(target_arch = "wasm32")]
+fn storage_key(key: &str) -> String {
+    format!("dioxus_file_picker:{key}")
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
+
+/// The session stored for `key`, if any.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn load(key: &str) -> Option<PickerSession> {
+    let value = local_storage()?
+        .get_item(&storage_key(key))
+        .ok()
+        .flatten()?;
+    serde_json::from_str(&value)
+        .inspect_err(|err| warn!("Ignoring the corrupt file picker session \"{key}\": {err}"))
+        .ok()
+}
+
+/// Stores `session` for `key`. Failures are logged, a picker works the same without its session.
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn store(key: &str, mut session: PickerSession) {
+    let Some(storage) = local_storage() else {
+        warn!("No localStorage to store the file picker session in");
+        return;
+    };
+    session.limit_history();
+    let value = serde_json::to_string(&session).expect("A session always serializes");
+    if let Err(err) = storage.set_item(&storage_key(key), &value) {
+        warn!("Could not store the file picker session \"{key}\": {err:?}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_history(history: &[&str], history_position: usize) -> PickerSession {
+        PickerSession {
+            history: history.iter().map(PathBuf::from).collect(),
+            history_position,
+            ..PickerSession::default()
+        }
+    }
+
+    #[test]
+    fn last_dir_follows_the_position() {
+        assert_eq!(with_history(&[], 0).last_dir(), None);
+        assert_eq!(
+            with_history(&["/a", "/b"], 0).last_dir(),
+            Some(Path::new("/b"))
+        );
+        assert_eq!(
+            with_history(&["/a", "/b"], 1).last_dir(),
+            Some(Path::new("/a"))
+        );
+        assert_eq!(with_history(&["/a", "/b"], 2).last_dir(), None);
+    }
+
+    #[test]
+    fn visit_appends_to_the_history() {
+        let mut session = with_history(&["/a"], 0);
+        session.visit(PathBuf::from("/b"));
+        assert_eq!(session, with_history(&["/a", "/b"], 0));
+    }
+
+    #[test]
+    fn visit_drops_the_forward_history() {
+        let mut session = with_history(&["/a", "/b", "/c"], 2);
+        session.visit(PathBuf::from("/d"));
+        assert_eq!(session, with_history(&["/a", "/d"], 0));
+        assert_eq!(session.last_dir(), Some(Path::new("/d")));
+    }
+
+    #[test]
+    fn visiting_the_current_dir_again_is_not_recorded() {
+        let mut session = with_history(&["/a", "/b"], 0);
+        session.visit(PathBuf::from("/b"));
+        assert_eq!(session, with_history(&["/a", "/b"], 0));
+        let mut session = with_history(&["/a", "/b"], 1);
+        session.visit(PathBuf::from("/a"));
+        assert_eq!(session, with_history(&["/a"], 0));
+    }
+
+    #[test]
+    fn the_stored_history_is_limited() {
+        let dirs: Vec<String> = (0..MAX_HISTORY + 10).map(|i| format!("/{i}")).collect();
+        let dirs: Vec<&str> = dirs.iter().map(String::as_str).collect();
+        let mut session = with_history(&dirs, 3);
+        session.limit_history();
+        assert_eq!(session, with_history(&dirs[10..], 3));
+    }
+
+    #[test]
+    fn limiting_the_history_keeps_the_current_dir() {
+        let dirs: Vec<String> = (0..MAX_HISTORY + 10).map(|i| format!("/{i}")).collect();
+        let dirs: Vec<&str> = dirs.iter().map(String::as_str).collect();
+        let mut session = with_history(&dirs, MAX_HISTORY + 5);
+        session.limit_history();
+        assert_eq!(session.last_dir(), Some(Path::new("/4")));
+        assert_eq!(
+            session,
+            with_history(&dirs[4..MAX_HISTORY + 4], MAX_HISTORY - 1)
+        );
+    }
+}
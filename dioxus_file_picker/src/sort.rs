@@ -0,0 +1,214 @@
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+/// How the integrated file picker orders entries.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SortOptions {
+    pub by: SortBy,
+    pub order: SortOrder,
+    /// If true, directories come before files, whatever the order.
+    pub folders_first: bool,
+}
+
+impl Default for SortOptions {
+    fn default() -> Self {
+        Self {
+            by: SortBy::default(),
+            order: SortOrder::default(),
+            folders_first: true,
+        }
+    }
+}
+
+/// What entries are sorted by. Ties are broken by name.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SortBy {
+    /// Alphabetical in the user's locale and case-insensitive, with runs of digits compared by their value, so `file2`
+    /// comes before `file10`.
+    #[default]
+    Name,
+    /// Directories count as empty.
+    Size,
+    /// When the entry was last modified.
+    Modified,
+    /// The extension. Directories have none.
+    Type,
+}
+
+impl SortBy {
+    pub const ALL: [SortBy; 4] = [SortBy::Name, SortBy::Size, SortBy::Modified, SortBy::Type];
+
+    /// The name shown in the picker.
+    pub fn label(self) -> &'static str {
+        match self {
+            SortBy::Name => "Name",
+            SortBy::Size => "Size",
+            SortBy::Modified => "Modified",
+            SortBy::Type => "Type",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SortOrder {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+impl SortOrder {
+    /// The other order.
+    pub fn reversed(self) -> Self {
+        match self {
+            SortOrder::Ascending => SortOrder::Descending,
+            SortOrder::Descending => SortOrder::Ascending,
+        }
+    }
+
+    /// Applies the order to an ascending comparison.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn apply(self, ordering: Ordering) -> Ordering {
+        match self {
+            SortOrder::Ascending => ordering,
+            SortOrder::Descending => ordering.reverse(),
+        }
+    }
+}
+
+/// Compares names the way people read them: alphabetically by the collation rules of the user's locale, ignoring
+/// case, with runs of ASCII digits compared by their value. Names that only differ in case are ordered by their exact
+/// characters, so the order is total.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn natural_cmp(a: &str, b: &str) -> Ordering {
+    COLLATOR.with(|collator| {
+        let mut left = digit_runs(a);
+        let mut right = digit_runs(b);
+        loop {
+            let ordering = match (left.next(), right.next()) {
+                (None, None) => return a.cmp(b),
+                (None, Some(_)) => return Ordering::Less,
+                (Some(_), None) => return Ordering::Greater,
+                (Some((l, true)), Some((r, true))) => compare_numbers(l, r),
+                (Some((l, _)), Some((r, _))) => collator.compare(l, r),
+            };
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+    })
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+thread_local! {
+    /// Created once per thread, as loading the rules of a locale is not free.
+    static COLLATOR: icu_collator::CollatorBorrowed<'static> = collator();
+}
+
+/// A collator for the user's locale, or the root collation, which still sorts e.g. `é` with `e`, if it is unknown.
+/// Only letters and accents count, not case.
+#[cfg(not(target_arch = "wasm32"))]
+fn collator() -> icu_collator::CollatorBorrowed<'static> {
+    use icu_collator::{
+        CollatorBorrowed, CollatorPreferences,
+        options::{CollatorOptions, Strength},
+    };
+    // POSIX locales look like `de_DE.UTF-8@euro`
+    let prefs = sys_locale::get_locale()
+        .and_then(|locale| {
+            let tag = locale.split(['.', '@']).next()?.replace('_', "-");
+            icu_locale_core::Locale::try_from_str(&tag).ok()
+        })
+        .map(|locale| CollatorPreferences::from(&locale))
+        .unwrap_or_default();
+    let mut options = CollatorOptions::default();
+    options.strength = Some(Strength::Secondary);
+    CollatorBorrowed::try_new(prefs, options)
+        .or_else(|_| CollatorBorrowed::try_new(CollatorPreferences::default(), options))
+        .expect("The root collation is compiled in")
+}
+
+/// Splits `name` into runs of ASCII digits and runs of everything else, each with whether it is digits.
+#[cfg(not(target_arch = "wasm32"))]
+fn digit_runs(name: &str) -> impl Iterator<Item = (&str, bool)> {
+    let mut rest = name;
+    std::iter::from_fn(move || {
+        let is_digits = rest.chars().next()?.is_ascii_digit();
+        let end = rest
+            .find(|c: char| c.is_ascii_digit() != is_digits)
+            .unwrap_or(rest.len());
+        let (run, remaining) = rest.split_at(end);
+        rest = remaining;
+        Some((run, is_digits))
+    })
+}
+
+/// Compares runs of digits by value, without parsing, so any length works. `007` and `7` are equal here.
+#[cfg(not(target_arch = "wasm32"))]
+fn compare_numbers(a: &str, b: &str) -> Ordering {
+    let a = a.trim_start_matches('0');
+    let b = b.trim_start_matches('0');
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_compare_by_value() {
+        assert_eq!(compare_numbers("2", "10"), Ordering::Less);
+        assert_eq!(compare_numbers("007", "7"), Ordering::Equal);
+        assert_eq!(compare_numbers("0", ""), Ordering::Equal);
+        assert_eq!(compare_numbers("19", "20"), Ordering::Less);
+        assert_eq!(
+            compare_numbers("123456789012345678901234567890", "99"),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn digit_runs_sort_naturally() {
+        let mut names = vec!["file10", "file2", "file1", "file02b", "file2a"];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(names, ["file1", "file2", "file2a", "file02b", "file10"]);
+    }
+
+    #[test]
+    fn case_is_ignored_until_it_is_the_only_difference() {
+        assert_eq!(natural_cmp("apple", "Banana"), Ordering::Less);
+        assert_eq!(natural_cmp("Readme", "readme"), Ordering::Less);
+        assert_eq!(natural_cmp("readme", "readme"), Ordering::Equal);
+        assert_eq!(natural_cmp("ÄRGER", "ärger"), Ordering::Less);
+    }
+
+    #[test]
+    fn prefixes_come_first() {
+        assert_eq!(natural_cmp("file", "file1"), Ordering::Less);
+        assert_eq!(natural_cmp("file1", "file"), Ordering::Greater);
+        assert_eq!(natural_cmp("", "a"), Ordering::Less);
+    }
+
+    #[test]
+    fn names_split_into_digit_runs() {
+        assert_eq!(
+            digit_runs("file10b2").collect::<Vec<_>>(),
+            [("file", false), ("10", true), ("b", false), ("2", true)]
+        );
+        assert_eq!(digit_runs("").count(), 0);
+    }
+
+    #[test]
+    fn accented_letters_sort_with_their_base_letter() {
+        let mut names = vec!["zebra", "edit", "école", "ecole", "Émile", "apple"];
+        names.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(names, ["apple", "ecole", "école", "edit", "Émile", "zebra"]);
+    }
+
+    #[test]
+    fn leading_zeros_only_break_ties() {
+        assert_eq!(natural_cmp("file007", "file7"), Ordering::Less);
+        assert_eq!(natural_cmp("file007", "file8"), Ordering::Less);
+    }
+}
@@ -0,0 +1,291 @@
+use std::{
+    fs,
+    io::{self, BufRead, Cursor, Seek},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+use dioxus::{logger::tracing::debug, prelude::*};
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader, imageops::FilterType};
+use tokio::sync::Semaphore;
+
+/// The edge of the `normal` size in the spec, large enough for the 100px grid icons
+const SIZE: u32 = 128;
+/// Larger images are not decoded, that would take too long and too much memory
+const MAX_FILE_SIZE: u64 = 64 * 1024 * 1024;
+/// How many thumbnails are made at once
+static PERMITS: Semaphore = Semaphore::const_new(4);
+
+/// Shows a thumbnail of the image at `path`, made in the background, or the generic file icon until it is ready or
+/// when the file is not an image.
+#[component]
+pub(crate) fn FileIcon(
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    /// If false, `path` is not on the local disk and never gets a thumbnail
+    is_local: bool,
+    #[props(default)] deactivated: bool,
+    onclick: Option<EventHandler<MouseEvent>>,
+) -> Element {
+    let thumbnail = use_resource(use_reactive!(|(path, modified, is_local)| async move {
+        let modified = modified?;
+        if !is_local || !is_supported(&path) {
+            return None;
+        }
+        // Waiting for a permit is cancelled with the component, e.g. when navigating away
+        let _permit = PERMITS.acquire().await.ok()?;
+        tokio::task::spawn_blocking(move || {
+            thumbnail(&path, modified)
+                .inspect_err(|err| debug!("No thumbnail for {}: {err}", path.display()))
+                .ok()
+        })
+        .await
+        .ok()
+        .flatten()
+    }));
+    let deactivated = if deactivated { "deactivated" } else { "" };
+    match thumbnail.read().as_ref() {
+        Some(Some(data_uri)) => rsx! {
+            img {
+                class: "fp-thumbnail {deactivated}",
+                src: "{data_uri}",
+                onclick: move |event| {
+                    if let Some(onclick) = onclick {
+                        onclick.call(event);
+                    }
+                },
+            }
+        },
+        _ => rsx! {
+            i {
+                class: "material-icons {deactivated}",
+                onclick: move |event| {
+                    if let Some(onclick) = onclick {
+                        onclick.call(event);
+                    }
+                },
+                "description"
+            }
+        },
+    }
+}
+
+/// If `path` has the extension of an image format that can be decoded.
+pub(crate) fn is_supported(path: &Path) -> bool {
+    ImageFormat::from_path(path).is_ok_and(|format| format.reading_enabled())
+}
+
+/// The thumbnail of the image at `path` as a png data uri. Thumbnails are cached as the freedesktop thumbnail spec
+/// (<https://specifications.freedesktop.org/thumbnail-spec/latest/>) describes, so the ones file managers made are
+/// reused and ours survive restarts.
+fn thumbnail(path: &Path, modified: SystemTime) -> io::Result<String> {
+    let uri = file_uri(path);
+    let mtime = modified
+        .duration_since(UNIX_EPOCH)
+        .map_err(io::Error::other)?
+        .as_secs();
+    let cached = cache_file(&uri);
+    let png = match cached
+        .as_deref()
+        .and_then(|file| read_cached(file, &uri, mtime))
+    {
+        Some(png) => png,
+        None => {
+            let png = render(path, &uri, mtime)?;
+            if let Some(file) = cached
+                && let Err(err) = write_cached(&file, &png)
+            {
+                debug!("Could not cache the thumbnail of {}: {err}", path.display());
+            }
+            png
+        }
+    };
+    Ok(format!("data:image/png;base64,{}", STANDARD.encode(png)))
+}
+
+/// `$XDG_CACHE_HOME/thumbnails/normal/<md5 of the uri>.png`
+fn cache_file(uri: &str) -> Option<PathBuf> {
+    Some(
+        dirs::cache_dir()?
+            .join("thumbnails")
+            .join("normal")
+            .join(cache_name(uri)),
+    )
+}
+
+fn cache_name(uri: &str) -> String {
+    format!("{:x}.png", md5::compute(uri))
+}
+
+/// The cached png, if it was made of the same file at the same modification time.
+fn read_cached(file: &Path, uri: &str, mtime: u64) -> Option<Vec<u8>> {
+    let png = fs::read(file).ok()?;
+    let mut reader = png::Decoder::new(Cursor::new(&png)).read_info().ok()?;
+    // Text chunks may come after the image data
+    let mut buffer = vec![0; reader.output_buffer_size()?];
+    reader.next_frame(&mut buffer).ok()?;
+    reader.finish().ok()?;
+    let text = &reader.info().uncompressed_latin1_text;
+    let field = |keyword: &str| {
+        text.iter()
+            .find(|chunk| chunk.keyword == keyword)
+            .map(|chunk| chunk.text.as_str())
+    };
+    let is_current = field("Thumb::URI") == Some(uri)
+        && field("Thumb::MTime").and_then(|text| text.parse().ok()) == Some(mtime);
+    is_current.then_some(png)
+}
+
+/// Decodes the image, turned upright, and encodes it downscaled as a png with the fields the spec requires.
+fn render(path: &Path, uri: &str, mtime: u64) -> io::Result<Vec<u8>> {
+    let size = fs::metadata(path)?.len();
+    if size > MAX_FILE_SIZE {
+        return Err(io::Error::other("The image is too large"));
+    }
+    let image = decode_upright(ImageReader::open(path)?)?;
+    let thumbnail = if image.width() > SIZE || image.height() > SIZE {
+        image.thumbnail(SIZE, SIZE)
+    } else {
+        image
+    }
+    .into_rgba8();
+
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, thumbnail.width(), thumbnail.height());
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let text = [
+        ("Thumb::URI", uri.to_owned()),
+        ("Thumb::MTime", mtime.to_string()),
+        ("Thumb::Size", size.to_string()),
+        ("Software", env!("CARGO_PKG_NAME").to_owned()),
+    ];
+    for (keyword, value) in text {
+        encoder
+            .add_text_chunk(keyword.to_owned(), value)
+            .map_err(io::Error::other)?;
+    }
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer
+        .write_image_data(thumbnail.as_raw())
+        .map_err(io::Error::other)?;
+    writer.finish().map_err(io::Error::other)?;
+    Ok(png)
+}
+
+/// The image in `data`, turned upright and downscaled to fit `edge`, as a png data uri.
+pub(crate) fn preview(data: &[u8], edge: u32) -> io::Result<String> {
+    let image = decode_upright(ImageReader::new(Cursor::new(data)))?;
+    let image = if image.width() > edge || image.height() > edge {
+        image.resize(edge, edge, FilterType::Triangle)
+    } else {
+        image
+    };
+    let mut png = Vec::new();
+    image
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .map_err(io::Error::other)?;
+    Ok(format!("data:image/png;base64,{}", STANDARD.encode(png)))
+}
+
+/// Decodes the image, rotated and flipped as its EXIF orientation says.
+fn decode_upright<R: BufRead + Seek>(reader: ImageReader<R>) -> io::Result<DynamicImage> {
+    let mut decoder = reader
+        .with_guessed_format()?
+        .into_decoder()
+        .map_err(io::Error::other)?;
+    let orientation = decoder.orientation().map_err(io::Error::other)?;
+    let mut image = DynamicImage::from_decoder(decoder).map_err(io::Error::other)?;
+    image.apply_orientation(orientation);
+    Ok(image)
+}
+
+/// Writes through a temporary file, so other apps never read a partial thumbnail. The spec asks for the cache to be
+/// private to the user.
+fn write_cached(file: &Path, png: &[u8]) -> io::Result<()> {
+    let dir = file.parent().expect("Cache files are in a directory");
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(dir)?;
+    let temp = file.with_extension(format!("{}.tmp", std::process::id()));
+    fs::write(&temp, png)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&temp, fs::Permissions::from_mode(0o600))?;
+    }
+    fs::rename(&temp, file)
+}
+
+/// The `file://` uri of an absolute path, escaped like GLib escapes it, since the cache is keyed by its exact text.
+fn file_uri(path: &Path) -> String {
+    const ALLOWED: &[u8] = b"-._~!$&'()*+,;=:@/";
+    #[cfg(unix)]
+    let bytes = std::os::unix::ffi::OsStrExt::as_bytes(path.as_os_str()).to_vec();
+    #[cfg(not(unix))]
+    let bytes = path.to_string_lossy().replace('\\', "/").into_bytes();
+    let mut uri = String::from("file://");
+    #[cfg(not(unix))]
+    uri.push('/');
+    for byte in bytes {
+        if byte.is_ascii_alphanumeric() || ALLOWED.contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_cache_is_keyed_by_the_md5_of_the_uri() {
+        // The example of the freedesktop thumbnail spec
+        assert_eq!(
+            cache_name("file:///home/jens/photos/me.png"),
+            "c6ee772d9e49320e97ec29a7eb5b1697.png"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn uris_are_escaped_like_glib() {
+        assert_eq!(
+            file_uri(Path::new("/home/user/a b/ü#1.png")),
+            "file:///home/user/a%20b/%C3%BC%231.png"
+        );
+        assert_eq!(
+            file_uri(Path::new("/tmp/keep-._~!$&'()*+,;=:@")),
+            "file:///tmp/keep-._~!$&'()*+,;=:@"
+        );
+        assert_eq!(file_uri(Path::new("/100%")), "file:///100%25");
+    }
+
+    #[test]
+    fn only_a_thumbnail_of_the_same_file_and_mtime_is_read() {
+        let dir = std::env::temp_dir().join(format!(
+            "dioxus_file_picker_{}_thumbnails",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let image = dir.join("image.png");
+        image::RgbaImage::new(300, 200).save(&image).unwrap();
+        let uri = file_uri(&image);
+        let png = render(&image, &uri, 42).unwrap();
+        let cached = dir.join("cache").join(cache_name(&uri));
+        write_cached(&cached, &png).unwrap();
+
+        assert_eq!(read_cached(&cached, &uri, 42), Some(png));
+        // The file changed since
+        assert_eq!(read_cached(&cached, &uri, 43), None);
+        assert_eq!(read_cached(&cached, "file:///other.png", 42), None);
+        assert_eq!(read_cached(&dir.join("missing.png"), &uri, 42), None);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
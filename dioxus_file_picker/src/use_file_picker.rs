@@ -0,0 +1,796 @@
+use std::{
+    env, fmt,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+#[cfg(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+use dioxus::desktop::DesktopService;
+use dioxus::{logger::tracing::debug, prelude::*};
+use futures_channel::oneshot;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::file_picker;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::file_saver;
+#[cfg(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "linux",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+use crate::{
+    native_dialog::{self, NativeDialogGuard},
+    picker_window::{self, WindowGeometry},
+};
+
+use crate::{
+    Accept, Backend, BackendSupport, FileSystem, Overlay, OverlaySize, PickerWindow, SaveTarget,
+    SelectionKind, SortOptions, VirtualPaths, save_target::enforce_extension, session,
+};
+
+/// Options for a single [FilePickerHandle::open] call.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PickOptions {
+    /// If true, on desktop will launch a native file picker.
+    pub desktop_native: bool,
+    /// If true, on desktop will launch in a new window. This is treated as true if `desktop_native` is true.
+    pub desktop_windowed: bool,
+    /// Can select multiple
+    pub multiple: bool,
+    /// The files to accept. Accepts everything if empty.
+    pub accept: Accept,
+    /// Whether files, directories or both can be selected.
+    pub selection: SelectionKind,
+    /// The path to open the file picker at. If None, defaults to current directory. Has no effect on web.
+    pub open_at: Option<PathBuf>,
+    /// The window of the integrated file picker, if it opens in a new window.
+    pub window: PickerWindow,
+    /// How much of the window the integrated file picker covers, if it opens in an overlay.
+    pub overlay_size: OverlaySize,
+    /// If Some, the directory and navigation history the user leaves the picker with are stored under this key and
+    /// restored the next time a picker with the same key opens without `open_at`. On web the browser reopens at the
+    /// last directory itself, the directories picked from are recorded in localStorage.
+    pub persistence_key: Option<String>,
+    /// What the integrated file picker browses. Defaults to the local disk. Any other file system is always shown in
+    /// the integrated file picker, as native dialogs can only browse the local disk. Has no effect on web.
+    pub file_system: FileSystem,
+    /// If not empty, the only directories the user can browse and pick from. The picker never goes above them and
+    /// symlinks resolving outside them are hidden. Native dialogs can't be restricted, so the integrated file picker is
+    /// always used. Has no effect on web.
+    pub roots: Vec<PathBuf>,
+    /// How the integrated file picker orders entries until the user changes it. With a `persistence_key`, the sort
+    /// the user chose last is used instead. Native dialogs and the browser sort themselves.
+    pub sort: SortOptions,
+}
+
+/// Options for a single [FilePickerHandle::save] call.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SaveOptions {
+    /// If true, on desktop will launch a native save dialog.
+    pub desktop_native: bool,
+    /// If true, on desktop will launch in a new window. This is treated as true if `desktop_native` is true.
+    pub desktop_windowed: bool,
+    /// The file name suggested to the user. On web this is the name of the download.
+    pub default_name: Option<String>,
+    /// The extension, e.g. `csv`, the file name is forced to end with.
+    pub extension: Option<String>,
+    /// If true, the default, the user has to confirm choosing a file that already exists. Native dialogs always
+    /// confirm. Has no effect on web.
+    pub confirm_overwrite: bool,
+    /// The path to open the save dialog at. If None, defaults to current directory. Has no effect on web.
+    pub open_at: Option<PathBuf>,
+    /// The window of the integrated save dialog, if it opens in a new window.
+    pub window: PickerWindow,
+    /// How much of the window the integrated save dialog covers, if it opens in an overlay.
+    pub overlay_size: OverlaySize,
+}
+
+impl Default for SaveOptions {
+    fn default() -> Self {
+        Self {
+            desktop_native: false,
+            desktop_windowed: false,
+            default_name: None,
+            extension: None,
+            confirm_overwrite: true,
+            open_at: None,
+            window: PickerWindow::default(),
+            overlay_size: OverlaySize::default(),
+        }
+    }
+}
+
+/// The result of a [FilePickerHandle::open] or [FilePickerHandle::save] call. Every backend reports the same way, so
+/// "the user backed out" can be told apart from "the user picked nothing" and "the picker failed".
+pub enum PickOutcome<T = VirtualPaths> {
+    /// The user submitted a selection: the paths picked, or where to save. If `multiple` is false, the set of paths
+    /// may be empty or contain one.
+    Selected(T),
+    /// The user backed out, or the pick was replaced by another pick on the same handle.
+    Cancelled,
+    /// The picker could not be shown or failed while open.
+    Failed(PickError),
+}
+
+/// Why a pick failed, see [PickOutcome::Failed].
+#[derive(Clone, Debug, PartialEq)]
+pub enum PickError {
+    /// The picker could not be launched, e.g. the browser refused to open the file input.
+    Launch(String),
+    /// Another native dialog is already open. Only one is shown at a time.
+    Busy,
+}
+
+impl fmt::Display for PickError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PickError::Launch(reason) => write!(f, "Could not launch the file picker: {reason}"),
+            PickError::Busy => f.write_str("Another native file dialog is already open"),
+        }
+    }
+}
+
+impl std::error::Error for PickError {}
+
+/// Creates a handle to open a file picker imperatively, e.g. from a menu item, a keyboard shortcut or in the middle
+/// of an async workflow. The element returned by [FilePickerHandle::render] must be part of the component's output,
+/// it hosts the web `<input>` and the overlay.
+///
+/// ```rust,ignore
+/// let picker = use_file_picker();
+/// rsx! {
+///     button {
+///         onclick: move |_| async move {
+///             if let PickOutcome::Selected(paths) = picker.open(PickOptions::default()).await {
+///                 info!("Selected: {:?}", paths.paths());
+///             }
+///         },
+///         "Open"
+///     }
+///     {picker.render()}
+/// }
+/// ```
+pub fn use_file_picker() -> FilePickerHandle {
+    let state = use_signal(PickerState::new);
+    let on_submit = use_callback(move |paths: VirtualPaths| {
+        resolve(state, Ending::Picked(paths));
+    });
+    let on_save = use_callback(move |target: SaveTarget| {
+        resolve(state, Ending::Saved(target));
+    });
+    let on_cancel = use_callback(move |()| {
+        resolve(state, Ending::Cancelled);
+    });
+    // A picker window outliving the component that opened it could never be closed again, and a modal one would keep
+    // its parent from taking input
+    use_drop(move || resolve(state, Ending::Cancelled));
+    FilePickerHandle {
+        state,
+        on_submit,
+        on_save,
+        on_cancel,
+    }
+}
+
+/// A handle to a file picker, see [use_file_picker].
+#[derive(Clone, Copy, PartialEq)]
+pub struct FilePickerHandle {
+    state: Signal<PickerState>,
+    /// Passed to the integrated file picker, so it resolves the pick in progress
+    on_submit: Callback<VirtualPaths, ()>,
+    /// Passed to the integrated save dialog, so it resolves the save in progress
+    on_save: Callback<SaveTarget, ()>,
+    /// Passed to the integrated file picker and save dialog, so they cancel what is in progress
+    on_cancel: Callback<(), ()>,
+}
+
+/// What the handle was last asked for.
+enum Request {
+    Pick(PickOptions),
+    Save(SaveOptions),
+}
+
+impl Request {
+    fn overlay_size(&self) -> OverlaySize {
+        match self {
+            Request::Pick(options) => options.overlay_size,
+            Request::Save(options) => options.overlay_size,
+        }
+    }
+}
+
+/// Whoever waits on the pick or save in progress.
+enum Pending {
+    Pick(oneshot::Sender<PickOutcome>),
+    Save(oneshot::Sender<PickOutcome<SaveTarget>>),
+}
+
+/// How a pick or save ended.
+enum Ending {
+    Picked(VirtualPaths),
+    Saved(SaveTarget),
+    Cancelled,
+    Failed(PickError),
+}
+
+impl Pending {
+    /// Hands `ending` to the waiting caller. What does not fit the request, e.g. paths for a save, cancels it.
+    fn send(self, ending: Ending) {
+        match self {
+            Pending::Pick(sender) => {
+                let _ = sender.send(match ending {
+                    Ending::Picked(paths) => PickOutcome::Selected(paths),
+                    Ending::Failed(error) => PickOutcome::Failed(error),
+                    Ending::Saved(_) | Ending::Cancelled => PickOutcome::Cancelled,
+                });
+            }
+            Pending::Save(sender) => {
+                let _ = sender.send(match ending {
+                    Ending::Saved(target) => PickOutcome::Selected(target),
+                    Ending::Failed(error) => PickOutcome::Failed(error),
+                    Ending::Picked(_) | Ending::Cancelled => PickOutcome::Cancelled,
+                });
+            }
+        }
+    }
+}
+
+struct PickerState {
+    /// What the handle was last asked for
+    request: Request,
+    /// Resolves the pick or save in progress, if any
+    sender: Option<Pending>,
+    /// Incremented for every pick, so the integrated file picker starts fresh each time
+    generation: u64,
+    overlay_active: bool,
+    #[cfg(any(
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    window: Option<Rc<DesktopService>>,
+    /// The window that launched a modal picker window, input to it is resumed when the pick ends
+    #[cfg(any(
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    modal_parent: Option<Rc<DesktopService>>,
+    /// The geometry of the last picker window, to reopen it the same way
+    #[cfg(any(
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    geometry: Option<WindowGeometry>,
+    #[cfg(target_arch = "wasm32")]
+    input_id: String,
+}
+
+impl PickerState {
+    fn new() -> Self {
+        Self {
+            request: Request::Pick(PickOptions::default()),
+            sender: None,
+            generation: 0,
+            overlay_active: false,
+            #[cfg(any(
+                target_os = "windows",
+                target_os = "macos",
+                target_os = "linux",
+                target_os = "dragonfly",
+                target_os = "freebsd",
+                target_os = "netbsd",
+                target_os = "openbsd"
+            ))]
+            window: None,
+            #[cfg(any(
+                target_os = "windows",
+                target_os = "macos",
+                target_os = "linux",
+                target_os = "dragonfly",
+                target_os = "freebsd",
+                target_os = "netbsd",
+                target_os = "openbsd"
+            ))]
+            modal_parent: None,
+            #[cfg(any(
+                target_os = "windows",
+                target_os = "macos",
+                target_os = "linux",
+                target_os = "dragonfly",
+                target_os = "freebsd",
+                target_os = "netbsd",
+                target_os = "openbsd"
+            ))]
+            geometry: None,
+            #[cfg(target_arch = "wasm32")]
+            input_id: uuid::Uuid::now_v7().to_string(),
+        }
+    }
+}
+
+/// Ends the pick or save in progress, if any, with `ending` and hides whatever ui was shown for it.
+fn resolve(mut state: Signal<PickerState>, ending: Ending) {
+    let mut writer = state.write();
+    writer.overlay_active = false;
+    #[cfg(any(
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    {
+        if let Some(window) = writer.window.take() {
+            writer.geometry = Some(WindowGeometry::of(&window));
+            window.close();
+        }
+        if let Some(parent) = writer.modal_parent.take() {
+            picker_window::set_parent_enabled(&parent, true);
+        }
+    }
+    if let Some(sender) = writer.sender.take() {
+        sender.send(ending);
+    }
+}
+
+impl FilePickerHandle {
+    /// Opens the file picker and waits until the user submits or backs out. A pick that is still in progress on this
+    /// handle is cancelled first.
+    pub async fn open(&self, options: PickOptions) -> PickOutcome {
+        let (sender, receiver) = oneshot::channel();
+        self.start(Request::Pick(options.clone()), Pending::Pick(sender));
+        self.launch(options).await;
+        receiver.await.unwrap_or(PickOutcome::Cancelled)
+    }
+
+    /// Opens a "Save as…" dialog and waits until the user chooses where to save or backs out. The app then writes its
+    /// data to the returned [SaveTarget]. A pick or save still in progress on this handle is cancelled first.
+    pub async fn save(&self, options: SaveOptions) -> PickOutcome<SaveTarget> {
+        let (sender, receiver) = oneshot::channel();
+        self.start(Request::Save(options.clone()), Pending::Save(sender));
+        self.launch_save(options).await;
+        receiver.await.unwrap_or(PickOutcome::Cancelled)
+    }
+
+    fn start(&self, request: Request, pending: Pending) {
+        self.cancel();
+        let mut state = self.state;
+        let mut writer = state.write();
+        writer.request = request;
+        writer.sender = Some(pending);
+        writer.generation += 1;
+    }
+
+    /// Returns true if a pick or save is in progress.
+    pub fn is_open(&self) -> bool {
+        self.state.read().sender.is_some()
+    }
+
+    /// Cancels the pick or save in progress, if any.
+    pub fn cancel(&self) {
+        resolve(self.state, Ending::Cancelled);
+    }
+
+    /// The element hosting the picker's ui. Must be rendered for [FilePickerHandle::open] to work.
+    pub fn render(&self) -> Element {
+        rsx! {
+            PickerHost { handle: *self }
+        }
+    }
+
+    // Web
+    #[cfg(target_arch = "wasm32")]
+    async fn launch(&self, options: PickOptions) {
+        let id = self.state.read().input_id.clone();
+        // The attributes are set here as well, since the input may not have been re-rendered with the new options yet
+        let script = format!(
+            "const input = document.getElementById({id:?});
+            input.multiple = {multiple};
+            input.webkitdirectory = {directory};
+            input.accept = {accept:?};
+            input.value = '';
+            input.click();",
+            multiple = options.multiple,
+            directory = options.selection == SelectionKind::Directories,
+            accept = options.accept.to_html_attribute(),
+        );
+        if let Err(err) = document::eval(&script).await {
+            resolve(
+                self.state,
+                Ending::Failed(PickError::Launch(format!("{err:?}"))),
+            );
+        }
+    }
+
+    // Web
+    #[cfg(target_arch = "wasm32")]
+    async fn launch_save(&self, options: SaveOptions) {
+        // Browsers do not let a page choose where a file is written, so the data is offered as a download instead
+        let file_name = enforce_extension(
+            options.default_name.as_deref().unwrap_or("download"),
+            options.extension.as_deref(),
+        );
+        resolve(self.state, Ending::Saved(SaveTarget::web(file_name)));
+    }
+
+    // Desktop
+    #[cfg(any(
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    async fn launch(&self, options: PickOptions) {
+        let mut backend = BackendSupport::resolve(Backend::requested(
+            options.desktop_native,
+            options.desktop_windowed,
+        ))
+        .await;
+        if backend == Backend::Native && options.selection == SelectionKind::FilesAndDirectories {
+            debug!(
+                "Native file dialogs cannot select both files and directories. Launching a dioxus file dialog instead"
+            );
+            backend = Backend::Windowed;
+        }
+        if backend == Backend::Native && !options.file_system.is_local() {
+            debug!(
+                "Native file dialogs can only browse the local disk. Launching a dioxus file dialog instead"
+            );
+            backend = Backend::Windowed;
+        }
+        if backend == Backend::Native && !options.roots.is_empty() {
+            debug!(
+                "Native file dialogs cannot be restricted to roots. Launching a dioxus file dialog instead"
+            );
+            backend = Backend::Windowed;
+        }
+        match backend {
+            Backend::Native => {
+                let Some(_guard) = NativeDialogGuard::acquire() else {
+                    resolve(self.state, Ending::Failed(PickError::Busy));
+                    return;
+                };
+                let mut session = options
+                    .persistence_key
+                    .as_deref()
+                    .and_then(session::load)
+                    .unwrap_or_default();
+                let path = options
+                    .open_at
+                    .clone()
+                    .or_else(|| session.last_dir().map(Path::to_path_buf))
+                    .unwrap_or_else(|| {
+                        env::current_dir().expect("Failed to get current directory")
+                    });
+                let dialog = rfd::AsyncFileDialog::new().set_directory(&path);
+                let dialog = native_dialog::add_accept_filters(dialog, &options.accept);
+                match native_dialog::pick(dialog, options.selection, options.multiple).await {
+                    Some(files) => {
+                        if let Some(key) = options.persistence_key.as_deref()
+                            && let Some(dir) = files.first().and_then(|file| file.parent())
+                        {
+                            session.visit(dir.to_path_buf());
+                            let key = key.to_owned();
+                            tokio::task::spawn_blocking(move || session::store(&key, session));
+                        }
+                        self.on_submit
+                            .call(VirtualPaths::native(files.into_iter().collect()))
+                    }
+                    None => self.on_cancel.call(()),
+                }
+            }
+            Backend::Windowed => {
+                let default_title = match options.selection {
+                    SelectionKind::Directories => "Select Folder",
+                    _ => "Open",
+                };
+                let dom = VirtualDom::new_with_props(
+                    file_picker::FilePicker,
+                    file_picker::FilePickerProps {
+                        multiple: options.multiple,
+                        accept: options.accept,
+                        selection: options.selection,
+                        open_at: options.open_at.clone(),
+                        on_submit: self.on_submit,
+                        on_cancel: Some(self.on_cancel),
+                        persistence_key: options.persistence_key.clone(),
+                        file_system: options.file_system.clone(),
+                        roots: options.roots.clone(),
+                        sort: options.sort,
+                    },
+                );
+                self.create_dioxus_window(dom, default_title, &options.window)
+                    .await
+            }
+            Backend::Overlay => {
+                let mut state = self.state;
+                state.write().overlay_active = true;
+            }
+        }
+    }
+
+    // Desktop
+    #[cfg(any(
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    async fn launch_save(&self, options: SaveOptions) {
+        let path = options
+            .open_at
+            .clone()
+            .unwrap_or_else(|| env::current_dir().expect("Failed to get current directory"));
+        let backend = BackendSupport::resolve(Backend::requested(
+            options.desktop_native,
+            options.desktop_windowed,
+        ))
+        .await;
+        match backend {
+            Backend::Native => {
+                let Some(_guard) = NativeDialogGuard::acquire() else {
+                    resolve(self.state, Ending::Failed(PickError::Busy));
+                    return;
+                };
+                let extension = options.extension.as_deref();
+                let mut dialog = rfd::AsyncFileDialog::new().set_directory(&path);
+                if let Some(default_name) = options.default_name.as_deref() {
+                    dialog = dialog.set_file_name(enforce_extension(default_name, extension));
+                }
+                if let Some(extension) = extension {
+                    let extension = extension.trim_start_matches('.');
+                    dialog = dialog.add_filter(format!(".{extension}"), &[extension]);
+                }
+                let Some(chosen) = native_dialog::save(dialog).await else {
+                    self.on_cancel.call(());
+                    return;
+                };
+                let file_name = chosen
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                let file = chosen.with_file_name(enforce_extension(&file_name, extension));
+                // The dialog only confirmed replacing the path chosen, not the one with the extension
+                if file != chosen && file.exists() && !native_dialog::confirm_replace(&file).await {
+                    self.on_cancel.call(());
+                    return;
+                }
+                self.on_save.call(SaveTarget::native(file));
+            }
+            Backend::Windowed => {
+                let dom = VirtualDom::new_with_props(
+                    file_saver::FileSaver,
+                    file_saver::FileSaverProps {
+                        default_name: options.default_name,
+                        extension: options.extension,
+                        confirm_overwrite: options.confirm_overwrite,
+                        open_at: Some(path),
+                        on_submit: self.on_save,
+                        on_cancel: Some(self.on_cancel),
+                    },
+                );
+                self.create_dioxus_window(dom, "Save As", &options.window)
+                    .await
+            }
+            Backend::Overlay => {
+                let mut state = self.state;
+                state.write().overlay_active = true;
+            }
+        }
+    }
+
+    #[cfg(any(
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    async fn create_dioxus_window(
+        &self,
+        dom: VirtualDom,
+        default_title: &str,
+        window: &PickerWindow,
+    ) {
+        let parent = dioxus::desktop::window();
+        let config = window.config(default_title, self.state.peek().geometry, &parent);
+        let opened = parent.new_window(dom, config).await;
+        let mut state = self.state;
+        let mut writer = state.write();
+        writer.window = Some(opened);
+        if window.is_modal() {
+            picker_window::set_parent_enabled(&parent, false);
+            writer.modal_parent = Some(parent);
+        }
+    }
+
+    // Mobile/fallback
+    #[cfg(not(any(
+        target_arch = "wasm32",
+        //
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    )))]
+    async fn launch(&self, _options: PickOptions) {
+        let mut state = self.state;
+        state.write().overlay_active = true;
+    }
+
+    // Mobile/fallback
+    #[cfg(not(any(
+        target_arch = "wasm32",
+        //
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    )))]
+    async fn launch_save(&self, _options: SaveOptions) {
+        let mut state = self.state;
+        state.write().overlay_active = true;
+    }
+}
+
+/// Renders the ui a [FilePickerHandle] needs in the component tree.
+#[component]
+fn PickerHost(handle: FilePickerHandle) -> Element {
+    // Web
+    #[cfg(target_arch = "wasm32")]
+    {
+        let state = handle.state.read();
+        // A save needs no input, it starts a download right away
+        let default_options = PickOptions::default();
+        let options = match &state.request {
+            Request::Pick(options) => options,
+            Request::Save(_) => &default_options,
+        };
+        let persistence_key = options.persistence_key.clone();
+        rsx! {
+            input {
+                id: state.input_id.clone(),
+                r#type: "file",
+                style: "display: none;",
+                multiple: options.multiple,
+                directory: options.selection == SelectionKind::Directories,
+                accept: options.accept.to_html_attribute(),
+                onchange: move |event: Event<FormData>| {
+                    event.prevent_default();
+                    if let Some(file_engine) = &event.files() {
+                        let paths = VirtualPaths::web(file_engine.clone());
+                        let picked = paths.paths();
+                        if let Some(key) = persistence_key.as_deref()
+                            && let Some(dir) = picked
+                                .iter()
+                                .filter_map(|path| Path::new(path).parent())
+                                .find(|dir| !dir.as_os_str().is_empty())
+                        {
+                            let mut session = session::load(key).unwrap_or_default();
+                            session.visit(dir.to_path_buf());
+                            session::store(key, session);
+                        }
+                        resolve(handle.state, Ending::Picked(paths));
+                    }
+                },
+                oncancel: move |_| handle.on_cancel.call(()),
+            }
+        }
+    }
+    // Desktop/mobile
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        // A window closed by the user cancels the pick
+        #[cfg(any(
+            target_os = "windows",
+            target_os = "macos",
+            target_os = "linux",
+            target_os = "dragonfly",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        ))]
+        dioxus::desktop::use_wry_event_handler(move |event, _| {
+            use dioxus::desktop::tao::event::{Event, WindowEvent};
+            if let Event::WindowEvent {
+                window_id,
+                event: WindowEvent::CloseRequested | WindowEvent::Destroyed,
+                ..
+            } = event
+            {
+                let is_picker_window = handle
+                    .state
+                    .peek()
+                    .window
+                    .as_ref()
+                    .is_some_and(|window| window.window.id() == *window_id);
+                if is_picker_window {
+                    handle.on_cancel.call(());
+                }
+            }
+        });
+        let overlay_active = use_memo(move || handle.state.read().overlay_active);
+        let state = handle.state.read();
+        let generation = state.generation;
+        rsx! {
+            Overlay {
+                active: overlay_active,
+                size: state.request.overlay_size(),
+                on_dismiss: handle.on_cancel,
+                // Only mounted while open, so every pick starts from its own options
+                if state.overlay_active {
+                    match state.request {
+                        Request::Pick(ref options) => rsx! {
+                            file_picker::FilePicker {
+                                key: "{generation}",
+                                multiple: options.multiple,
+                                accept: options.accept.clone(),
+                                selection: options.selection,
+                                open_at: options.open_at.clone(),
+                                on_submit: handle.on_submit,
+                                on_cancel: handle.on_cancel,
+                                persistence_key: options.persistence_key.clone(),
+                                file_system: options.file_system.clone(),
+                                roots: options.roots.clone(),
+                                sort: options.sort,
+                            }
+                        },
+                        Request::Save(ref options) => rsx! {
+                            file_saver::FileSaver {
+                                key: "{generation}",
+                                default_name: options.default_name.clone(),
+                                extension: options.extension.clone(),
+                                confirm_overwrite: options.confirm_overwrite,
+                                open_at: options.open_at.clone(),
+                                on_submit: handle.on_save,
+                                on_cancel: handle.on_cancel,
+                            }
+                        },
+                    }
+                }
+            }
+        }
+    }
+}
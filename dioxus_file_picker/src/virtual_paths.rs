@@ -14,33 +14,70 @@ mod web {
 
     /// Web implementation of virtual paths for WebAssembly targets
     pub struct VirtualPaths {
-        file_engine: Arc<dyn FileEngine>,
+        source: Source,
+    }
+
+    enum Source {
+        /// Files from a file input
+        FileEngine(Arc<dyn FileEngine>),
+        /// Files from a drop, with their path relative to what was dropped
+        Files(Vec<(String, web_sys::File)>),
     }
 
     impl VirtualPaths {
         pub(crate) fn web(file_engine: Arc<dyn FileEngine>) -> Self {
-            Self { file_engine }
+            Self {
+                source: Source::FileEngine(file_engine),
+            }
+        }
+
+        pub(crate) fn web_files(files: Vec<(String, web_sys::File)>) -> Self {
+            Self {
+                source: Source::Files(files),
+            }
         }
 
         /// Returns the number of paths.
         pub fn len(&self) -> usize {
-            self.file_engine.files().len()
+            match &self.source {
+                Source::FileEngine(file_engine) => file_engine.files().len(),
+                Source::Files(files) => files.len(),
+            }
         }
 
-        /// Returns the paths as `String`s. On web the path will be the file name.
+        /// Returns the paths as `String`s. On web the path will be the file name, or the path relative to a dropped
+        /// folder.
         pub fn paths(&self) -> HashSet<String> {
-            self.file_engine.files().iter().cloned().collect()
+            match &self.source {
+                Source::FileEngine(file_engine) => file_engine.files().iter().cloned().collect(),
+                Source::Files(files) => files.iter().map(|(path, _)| path.clone()).collect(),
+            }
         }
 
         /// Reads all the files from the paths.
         pub async fn read_files(&self) -> Vec<(String, Vec<u8>)> {
             let mut files_and_data = Vec::new();
 
-            for file_name in self.file_engine.files() {
-                if let Some(data) = self.file_engine.read_file(&file_name).await {
-                    files_and_data.push((file_name.clone(), data));
-                } else {
-                    dioxus::logger::tracing::warn!("Failed to read file: {:?}", file_name);
+            match &self.source {
+                Source::FileEngine(file_engine) => {
+                    for file_name in file_engine.files() {
+                        if let Some(data) = file_engine.read_file(&file_name).await {
+                            files_and_data.push((file_name.clone(), data));
+                        } else {
+                            dioxus::logger::tracing::warn!("Failed to read file: {:?}", file_name);
+                        }
+                    }
+                }
+                Source::Files(files) => {
+                    for (path, file) in files {
+                        match wasm_bindgen_futures::JsFuture::from(file.array_buffer()).await {
+                            Ok(buffer) => files_and_data
+                                .push((path.clone(), js_sys::Uint8Array::new(&buffer).to_vec())),
+                            Err(_) => {
+                                dioxus::logger::tracing::warn!("Failed to read file: {:?}", path)
+                            }
+                        }
+                    }
                 }
             }
 
@@ -53,17 +90,32 @@ mod web {
 #[cfg(not(target_arch = "wasm32"))]
 mod native {
     use dioxus::html::FileEngine;
-    use std::{collections::HashSet, path::PathBuf, sync::Arc};
+    use std::{collections::HashSet, io, path::PathBuf, sync::Arc};
     use tokio::fs;
 
+    use crate::FileSystem;
+
     /// Native implementation of virtual paths for non-WebAssembly targets
     pub struct VirtualPaths {
         paths: HashSet<PathBuf>,
+        /// Where the paths are, if not on the local disk
+        file_system: Option<FileSystem>,
     }
 
     impl VirtualPaths {
         pub(crate) fn native(paths: HashSet<PathBuf>) -> Self {
-            Self { paths }
+            Self {
+                paths,
+                file_system: None,
+            }
+        }
+
+        /// Paths picked from `file_system`, which are read through it.
+        pub(crate) fn native_in(paths: HashSet<PathBuf>, file_system: FileSystem) -> Self {
+            Self {
+                paths,
+                file_system: Some(file_system),
+            }
         }
 
         /// Returns the number of paths.
@@ -84,7 +136,17 @@ mod native {
             let mut files_and_data = Vec::new();
 
             for path in &self.paths {
-                if let Ok(data) = fs::read(&path).await {
+                let data = match &self.file_system {
+                    // Providers block, e.g. on a network request
+                    Some(file_system) => {
+                        let (file_system, path) = (file_system.clone(), path.clone());
+                        tokio::task::spawn_blocking(move || file_system.read(&path))
+                            .await
+                            .unwrap_or_else(|err| Err(io::Error::other(err)))
+                    }
+                    None => fs::read(&path).await,
+                };
+                if let Ok(data) = data {
                     files_and_data.push((path.to_string_lossy().into_owned(), data));
                 } else {
                     dioxus::logger::tracing::warn!("Failed to read file: {:?}", path);
@@ -0,0 +1,153 @@
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use dioxus::logger::tracing::debug;
+
+use crate::{FileSystem, FileSystemType, explorer::is_within_roots};
+
+/// An entry found below the root of a [walk].
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct WalkEntry {
+    pub(crate) path: PathBuf,
+    /// Relative to the root, with `/` separators
+    pub(crate) relative: String,
+    pub(crate) kind: FileSystemType,
+}
+
+/// Walks the tree below `root`, on the calling thread, calling `visit` with every entry until it returns false.
+/// Entries deeper than `max_depth` are not visited, the entries of `root` are at depth 1. Hidden entries are skipped,
+/// and on the local disk so is what `.gitignore` and `.ignore` files exclude.
+///
+/// With `roots`, symlinked directories are not walked into, and entries that resolve to a path outside the roots are
+/// skipped. Without, symlinks are followed, and a directory reached again through one is not walked twice, so cycles
+/// end.
+pub(crate) fn walk(
+    file_system: &FileSystem,
+    root: &Path,
+    max_depth: usize,
+    roots: &[PathBuf],
+    mut visit: impl FnMut(WalkEntry) -> bool,
+) {
+    let visit = |entry: WalkEntry| {
+        if !roots.is_empty() {
+            let resolved = file_system
+                .absolute(&entry.path)
+                .unwrap_or_else(|_| entry.path.clone());
+            if !is_within_roots(roots, &resolved) {
+                debug!("Skipping {}, it is outside the roots", entry.path.display());
+                return true;
+            }
+        }
+        visit(entry)
+    };
+    if file_system.is_local() {
+        walk_local(root, max_depth, roots.is_empty(), visit);
+    } else {
+        walk_provider(file_system, root, max_depth, roots, visit);
+    }
+}
+
+fn walk_local(
+    root: &Path,
+    max_depth: usize,
+    follow_links: bool,
+    mut visit: impl FnMut(WalkEntry) -> bool,
+) {
+    // Reports symlink cycles as errors instead of descending into them
+    let walker = ignore::WalkBuilder::new(root)
+        .max_depth(Some(max_depth))
+        .follow_links(follow_links)
+        .require_git(false)
+        .build();
+    for entry in walker {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(err) => {
+                debug!("Skipping while walking {}: {err}", root.display());
+                continue;
+            }
+        };
+        if entry.depth() == 0 {
+            continue;
+        }
+        let is_dir = match entry.file_type() {
+            Some(file_type) if file_type.is_symlink() => entry.path().is_dir(),
+            Some(file_type) => file_type.is_dir(),
+            None => false,
+        };
+        let path = entry.into_path();
+        let Some(relative) = relative(root, &path) else {
+            continue;
+        };
+        let kind = if is_dir {
+            FileSystemType::Directory
+        } else {
+            FileSystemType::File
+        };
+        if !visit(WalkEntry {
+            path,
+            relative,
+            kind,
+        }) {
+            return;
+        }
+    }
+}
+
+/// Walks through [crate::FileSystemProvider::read_dir], which resolves symlinks, so cycles are caught by the resolved
+/// paths of the directories walked. With `roots`, only directories resolving inside them are walked into.
+fn walk_provider(
+    file_system: &FileSystem,
+    root: &Path,
+    max_depth: usize,
+    roots: &[PathBuf],
+    mut visit: impl FnMut(WalkEntry) -> bool,
+) {
+    let mut walked = HashSet::from([root.to_path_buf()]);
+    let mut pending = vec![(root.to_path_buf(), String::new(), 0)];
+    while let Some((dir, dir_relative, depth)) = pending.pop() {
+        let entries = match file_system.read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(err) => {
+                debug!("Skipping {} while walking: {err}", dir.display());
+                continue;
+            }
+        };
+        for entry in entries.flatten() {
+            if entry.name.starts_with('.') {
+                continue;
+            }
+            let relative = if dir_relative.is_empty() {
+                entry.name.clone()
+            } else {
+                format!("{dir_relative}/{}", entry.name)
+            };
+            let kind = entry.metadata.kind;
+            if kind == FileSystemType::Directory
+                && depth + 1 < max_depth
+                && is_within_roots(roots, &entry.path)
+                && walked.insert(entry.path.clone())
+            {
+                pending.push((entry.path.clone(), relative.clone(), depth + 1));
+            }
+            if !visit(WalkEntry {
+                path: entry.path,
+                relative,
+                kind,
+            }) {
+                return;
+            }
+        }
+    }
+}
+
+fn relative(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    let segments: Vec<_> = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect();
+    Some(segments.join("/"))
+}